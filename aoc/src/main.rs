@@ -0,0 +1,119 @@
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::time::Duration;
+use rayon::prelude::*;
+use crate::runner::{build, discover_days, run_day, Day, DayResult};
+
+mod runner;
+
+const USAGE: &str = "usage: aoc run (--all | <day>...) [--jobs N] [--timeout SECS]";
+
+struct Options {
+    days: Vec<String>,
+    all: bool,
+    jobs: usize,
+    timeout: Option<Duration>,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) != Some("run") {
+        eprintln!("{USAGE}");
+        exit(2);
+    }
+    let options = parse_options(&args[1..]).unwrap_or_else(|e| {
+        eprintln!("{e}\n{USAGE}");
+        exit(2);
+    });
+
+    let root = workspace_root();
+    let days: Vec<Day> = discover_days(&root).into_iter()
+        .filter(|day| options.all || options.days.contains(&day.name))
+        .collect();
+    if let Some(missing) = options.days.iter().find(|name| !days.iter().any(|d| &d.name == *name)) {
+        eprintln!("No such day: {missing}");
+        exit(2);
+    }
+
+    // run the days with the same profile the runner was built with
+    let release = !cfg!(debug_assertions);
+    if let Err(e) = build(&root, &days, release) {
+        eprintln!("{e}");
+        exit(1);
+    }
+    let bin_dir = std::env::current_exe().unwrap().parent().unwrap().to_path_buf();
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.jobs)
+        .build()
+        .unwrap();
+    let results: Vec<DayResult> = pool.install(|| {
+        days.par_iter()
+            .map(|day| run_day(day, &bin_dir, options.timeout))
+            .collect()
+    });
+
+    print_summary(&results);
+    if results.iter().any(|r| r.status.is_failure()) {
+        exit(1);
+    }
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let jobs = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let mut options = Options { days: vec![], all: false, jobs, timeout: None };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        // accept both `--jobs 4` and `--jobs=4`
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value.to_string())),
+            None => (arg.as_str(), None)
+        };
+        let mut value = |name: &str| inline_value.clone()
+            .or_else(|| iter.next().cloned())
+            .ok_or(format!("{name} needs a value"));
+        match flag {
+            "--all" => options.all = true,
+            "--jobs" | "-j" => options.jobs = value("--jobs")?.parse()
+                .map_err(|_| String::from("--jobs should be a number"))?,
+            "--timeout" => options.timeout = Some(Duration::from_secs(value("--timeout")?.parse()
+                .map_err(|_| String::from("--timeout should be a number of seconds"))?)),
+            day if !day.starts_with('-') => options.days.push(String::from(day)),
+            other => return Err(format!("Unknown option {other}")),
+        }
+    }
+    match (options.all, options.days.is_empty()) {
+        (true, false) | (false, true) => return Err(String::from("Pick either --all or some days")),
+        _ => {}
+    }
+    if options.jobs == 0 {
+        return Err(String::from("--jobs should be at least 1"));
+    }
+    Ok(options)
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+fn print_summary(results: &[DayResult]) {
+    let answer_width = results.iter()
+        .flat_map(|r| r.answers.iter())
+        .map(|a| a.len())
+        .max()
+        .unwrap_or(0)
+        .max(6);
+    println!("{:<5} {:<answer_width$} {:<answer_width$} {:>9}  result", "day", "part 1", "part 2", "time");
+    for result in results {
+        let answer = |idx: usize| result.answers.get(idx).map(String::as_str).unwrap_or("-");
+        println!("{:<5} {:<answer_width$} {:<answer_width$} {:>8.3}s  {}",
+                 result.day, answer(0), answer(1),
+                 result.duration.as_secs_f64(), result.status.describe());
+    }
+
+    let failed = results.iter().filter(|r| r.status.is_failure()).count();
+    let unchecked = results.iter().filter(|r| r.status == runner::Status::Unchecked).count();
+    let total: f64 = results.iter().map(|r| r.duration.as_secs_f64()).sum();
+    println!("{} passed, {failed} failed, {unchecked} unchecked ({total:.3}s of work)",
+             results.len() - failed - unchecked);
+}
@@ -0,0 +1,252 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const ANSWERS_FILE: &str = "answers";
+
+#[derive(Debug, Clone)]
+pub struct Day {
+    pub name: String,
+    pub dir: PathBuf,
+    // the package name, which is also the binary's name
+    pub package: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Status {
+    Pass,
+    // (part, expected, got)
+    Fail(Vec<(usize, String, String)>),
+    Unchecked,
+    Panicked(String),
+    TimedOut(Duration),
+    Error(String),
+}
+
+impl Status {
+    pub fn is_failure(&self) -> bool {
+        !matches!(self, Status::Pass | Status::Unchecked)
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Status::Pass => String::from("pass"),
+            Status::Unchecked => String::from("no known answers"),
+            Status::Fail(mismatches) => {
+                let parts: Vec<String> = mismatches.iter()
+                    .map(|(part, expected, got)| format!("part {part} expected {expected}, got {got}"))
+                    .collect();
+                format!("FAIL: {}", parts.join("; "))
+            },
+            Status::Panicked(message) => format!("PANIC: {message}"),
+            Status::TimedOut(limit) => format!("TIMEOUT after {}s", limit.as_secs()),
+            Status::Error(message) => format!("ERROR: {message}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DayResult {
+    pub day: String,
+    pub answers: Vec<String>,
+    pub duration: Duration,
+    pub status: Status,
+}
+
+// every dNN directory in the workspace that has a Cargo.toml, in order
+pub fn discover_days(root: &Path) -> Vec<Day> {
+    let mut days: Vec<Day> = fs::read_dir(root)
+        .unwrap_or_else(|e| panic!("Couldn't read workspace {}: {e}", root.display()))
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_day_name(&path.file_name().unwrap().to_string_lossy()))
+        .flat_map(|dir| {
+            let manifest = fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+            let package = package_name(&manifest)?;
+            let name = dir.file_name().unwrap().to_string_lossy().to_string();
+            Some(Day { name, dir, package })
+        })
+        .collect();
+    days.sort_by(|a, b| a.name.cmp(&b.name));
+    days
+}
+
+fn is_day_name(name: &str) -> bool {
+    name.len() == 3 && name.starts_with('d') && name.chars().skip(1).all(|ch| ch.is_ascii_digit())
+}
+
+fn package_name(manifest: &str) -> Option<String> {
+    manifest.lines()
+        .find(|line| line.trim_start().starts_with("name"))
+        .and_then(|line| line.split('"').nth(1))
+        .map(String::from)
+}
+
+// build all of the days up-front, so the parallel runs don't fight over cargo's lock
+pub fn build(root: &Path, days: &[Day], release: bool) -> Result<(), String> {
+    let mut cargo = Command::new(std::env::var("CARGO").unwrap_or(String::from("cargo")));
+    cargo.current_dir(root).arg("build").arg("--quiet");
+    if release {
+        cargo.arg("--release");
+    }
+    for day in days {
+        cargo.arg("-p").arg(&day.package);
+    }
+    let status = cargo.status().map_err(|e| format!("Couldn't run cargo: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("cargo build failed with {status}"))
+    }
+}
+
+// run a day's binary from inside its directory, so it finds its `input`
+pub fn run_day(day: &Day, bin_dir: &Path, timeout: Option<Duration>) -> DayResult {
+    let begin = Instant::now();
+    let outcome = Command::new(bin_dir.join(&day.package))
+        .current_dir(&day.dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Couldn't start {}: {e}", day.package))
+        .and_then(|child| wait_with_timeout(child, timeout));
+    let duration = begin.elapsed();
+
+    let (answers, status) = match outcome {
+        Err(message) => (vec![], Status::Error(message)),
+        Ok(None) => (vec![], Status::TimedOut(timeout.unwrap())),
+        Ok(Some((success, stdout, stderr))) => {
+            let answers = parse_answers(&stdout);
+            let status = if success {
+                check_answers(&answers, known_answers(&day.dir))
+            } else {
+                Status::Panicked(panic_message(&stderr))
+            };
+            (answers, status)
+        }
+    };
+
+    DayResult { day: day.name.clone(), answers, duration, status }
+}
+
+// Some((exited successfully, stdout, stderr)), or None if we gave up waiting
+fn wait_with_timeout(mut child: Child, timeout: Option<Duration>)
+    -> Result<Option<(bool, String, String)>, String> {
+    // drain the pipes on their own threads, so a chatty day can't fill them up and stall
+    let stdout = drain(child.stdout.take().unwrap());
+    let stderr = drain(child.stderr.take().unwrap());
+
+    let begin = Instant::now();
+    let exit_status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        if timeout.map(|limit| begin.elapsed() > limit).unwrap_or(false) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+
+    Ok(Some((exit_status.success(), stdout.join().unwrap(), stderr.join().unwrap())))
+}
+
+fn drain(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut contents = String::new();
+        let _ = pipe.read_to_string(&mut contents);
+        contents
+    })
+}
+
+// Days print either `Part N: answer ...` or just the bare answer on its own line;
+// either way, the Nth answer-looking line is part N.
+pub fn parse_answers(stdout: &str) -> Vec<String> {
+    stdout.lines()
+        .map(|line| line.trim())
+        .flat_map(|line| {
+            if let Some(rest) = line.strip_prefix("Part ") {
+                let (_, answer) = rest.split_once(':')?;
+                answer.split_whitespace().next().map(String::from)
+            } else if !line.is_empty() && line.chars().all(|ch| ch.is_ascii_digit() || ch == '-') {
+                Some(String::from(line))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+// the `answers` file has the expected answer for each part, one per line
+fn known_answers(dir: &Path) -> Option<Vec<String>> {
+    let contents = fs::read_to_string(dir.join(ANSWERS_FILE)).ok()?;
+    Some(contents.lines().map(|l| String::from(l.trim())).filter(|l| !l.is_empty()).collect())
+}
+
+fn check_answers(answers: &[String], known: Option<Vec<String>>) -> Status {
+    let known = match known {
+        None => return Status::Unchecked,
+        Some(known) => known
+    };
+    let mismatches: Vec<(usize, String, String)> = known.iter().enumerate()
+        .map(|(idx, expected)| {
+            let got = answers.get(idx).cloned().unwrap_or(String::from("nothing"));
+            (idx + 1, expected.clone(), got)
+        })
+        .filter(|(_, expected, got)| expected != got)
+        .collect();
+    if mismatches.is_empty() {
+        Status::Pass
+    } else {
+        Status::Fail(mismatches)
+    }
+}
+
+// pull the message out of "thread 'main' panicked at src/main.rs:71:40:\n<message>"
+pub fn panic_message(stderr: &str) -> String {
+    let mut lines = stderr.lines();
+    while let Some(line) = lines.next() {
+        if line.contains("panicked at") {
+            let location = line.split("panicked at").nth(1).unwrap().trim().trim_end_matches(':');
+            let message = lines.next().unwrap_or("").trim();
+            return format!("{message} ({location})");
+        }
+    }
+    stderr.lines().last().map(String::from).unwrap_or(String::from("exited with an error"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_from_output() {
+        assert_eq!(parse_answers("54951\n55218\n"), vec!["54951", "55218"]);
+        assert_eq!(parse_answers("Part 1: 4811940\nPart 2: 30077773 in 32 ms\n"),
+                   vec!["4811940", "30077773"]);
+        assert_eq!(parse_answers("Got 12 labels\nPart 1: -3\n"), vec!["-3"]);
+    }
+
+    #[test]
+    fn checking() {
+        let answers = vec![String::from("1"), String::from("2")];
+        assert_eq!(check_answers(&answers, None), Status::Unchecked);
+        assert_eq!(check_answers(&answers, Some(vec![String::from("1"), String::from("2")])), Status::Pass);
+        assert_eq!(check_answers(&answers[..1], Some(vec![String::from("1"), String::from("2")])),
+                   Status::Fail(vec![(2, String::from("2"), String::from("nothing"))]));
+    }
+
+    #[test]
+    fn panics() {
+        let stderr = "\nthread 'main' (11796) panicked at d13/src/main.rs:71:40:\n\
+                      called `Option::unwrap()` on a `None` value\n\
+                      note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\n";
+        assert_eq!(panic_message(stderr),
+                   "called `Option::unwrap()` on a `None` value (d13/src/main.rs:71:40)");
+    }
+}
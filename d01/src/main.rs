@@ -1,91 +1,95 @@
-use std::fs;
-use regex::Regex;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use libaoc::config::Config;
+use libaoc::{debug, warn};
+use crate::report::{CalibrationReport, LineStatus};
+use crate::scanner::Scanner;
+use crate::vocabulary::{calibration_value, Vocabulary};
+
+mod report;
+mod scanner;
+mod vocabulary;
 
 fn main() {
+    let config = Config::load();
     part1();
-    part2();
+    part2(&config);
 }
 
 fn part1() {
-    let sum: u32 = fs::read_to_string("input")
-        .expect("Couldn't read file")
-        .lines()
-        .map(|line| line_to_int(line, first_digit_1))
-        .sum();
-
+    let sum = calibration_sum(open_input(), &Vocabulary::digits().scanner());
     println!("{sum}");
 }
 
-fn first_digit_1(line: &str) -> &str {
-    let first_num_re = Regex::new(r"\d").unwrap();
-    let digit_str = first_num_re
-        .find_iter(line)
-        .next()
-        .map(|d| d.as_str())
-        .unwrap();
+// `--report=part2.csv` also writes out how every line was read
+fn part2(config: &Config) {
+    let scanner = Vocabulary::from_config(config).scanner();
+    let report_file: String = config.get("report", String::new());
+    if report_file.is_empty() {
+        println!("{}", calibration_sum(open_input(), &scanner));
+        return;
+    }
 
-    return digit_str;
+    // the report keeps every line, so only build it when asked
+    let report = CalibrationReport::build(open_input(), &scanner);
+    report.write_csv(&report_file);
+    debug!("{} lines skipped, {} ambiguous, report in {report_file}",
+           report.with_status(LineStatus::Skipped).count(),
+           report.with_status(LineStatus::Ambiguous).count());
+    println!("{}", report.sum());
 }
 
-fn line_to_int(line: &str, digit_finder: fn(&str) -> &str) -> u32 {
-    let digits_1 = digit_finder(line).to_string();
-    let reverse_line: String = line.to_string().chars().rev().collect();
-    let digits_2 = digits_1 + digit_finder(&reverse_line);
+// read line by line rather than all at once, so the input can be bigger than memory
+fn open_input() -> BufReader<File> {
+    BufReader::new(File::open("input").expect("Couldn't read file"))
+}
 
-    return digits_2.parse().unwrap();
+// Reads into the same buffer line after line and only looks at the first and last matches,
+// so summing doesn't allocate anything per line (the report does, but only when asked for).
+fn calibration_sum(mut input: impl BufRead, scanner: &Scanner) -> u64 {
+    let mut sum = 0;
+    let mut line = String::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        if input.read_line(&mut line).expect("Couldn't read line") == 0 {
+            return sum;
+        }
+        line_number += 1;
+        let text = line.trim_end_matches(['\n', '\r']);
+        sum += line_to_int(line_number, text, scanner).unwrap_or(0);
+    }
 }
 
-fn part2() {
-    let sum:u32 = fs::read_to_string("input")
-        .expect("Couldn't read file")
-        .lines()
-        .map(|line| line_to_int_2(line))
-        .sum();
-    println!("{sum}");
+fn line_to_int(line_number: usize, line: &str, scanner: &Scanner) -> Option<u64> {
+    match scanner.first_and_last(line) {
+        Some((first, last)) => {
+            let two_digit = calibration_value(first.value, last.value);
+            debug!("{line}: {} {} => {two_digit}", &line[first.start..first.end], &line[last.start..last.end]);
+            Some(two_digit)
+        },
+        None => {
+            warn!("No digit in line {line_number}, skipping it: {line}");
+            None
+        }
+    }
 }
 
-fn line_to_int_2(line: &str) -> u32 {
-    let num_re = Regex::new(r"(\d|one|two|three|four|five|six|seven|eight|nine)")
-        .unwrap();
-    let first_digit = num_re
-        .find_iter(line)
-        .next()
-        .map(|d| d.as_str())
-        .map(parse_digit)
-        .unwrap()
-        .to_string();
-    let backwards_num_re = Regex::new(r"(\d|eno|owt|eerht|ruof|evif|xis|neves|thgie|enin)")
-        .unwrap();
-    let last_unparsed_digit = backwards_num_re
-        .find_iter(reverse(line).as_str())
-        .next()
-        .map(|m| m.as_str())
-        .map(reverse)
-        .unwrap();
-    let last_digit = parse_digit(last_unparsed_digit.as_str());
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    #[test]
+    fn examples() {
+        let part1 = "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet\n";
+        assert_eq!(calibration_sum(part1.as_bytes(), &Vocabulary::digits().scanner()), 142);
 
-    let combo = first_digit.clone() + last_digit;
-    let two_digit = combo.parse().unwrap();
-    // println!("{line}: {first_digit} {last_digit} => {combo} ({two_digit})");
-    return two_digit
-}
+        let part2 = "two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n\
+                     4nineeightseven2\nzoneight234\n7pqrstsixteen\n";
+        let english = Vocabulary::digits().language("english");
+        assert_eq!(calibration_sum(part2.as_bytes(), &english.scanner()), 281);
 
-fn parse_digit(d: &str) -> &str {
-    match d {
-        "one" => "1",
-        "two" => "2",
-        "three" => "3",
-        "four" => "4",
-        "five" => "5",
-        "six" => "6",
-        "seven" => "7",
-        "eight" => "8",
-        "nine" => "9",
-        _ => d
+        // lines without digits are left out instead of stopping everything
+        assert_eq!(calibration_sum("12\nnope\n34\n".as_bytes(), &english.scanner()), 46);
     }
 }
-
-fn reverse(word: &str) -> String {
-    return word.to_string().chars().rev().collect();
-}
\ No newline at end of file
@@ -0,0 +1,156 @@
+use std::fs::File;
+use std::io::{BufRead, BufWriter, Write};
+use crate::scanner::{ends, Match, Scanner};
+use crate::vocabulary::calibration_value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub text: String,
+    // byte offsets into the line, end exclusive
+    pub start: usize,
+    pub end: usize,
+    pub value: u64,
+}
+
+impl Token {
+    fn new(line: &str, m: &Match) -> Token {
+        Token { text: String::from(&line[m.start..m.end]), start: m.start, end: m.end, value: m.value }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineStatus {
+    Ok,
+    // the first or last token shares letters with another match, like the "two" in "eightwo",
+    // so the value depends on overlapping words counting
+    Ambiguous,
+    // nothing on the line matched
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineReport {
+    // counting from 1, like an editor does
+    pub line_number: usize,
+    pub tokens: Option<(Token, Token)>,
+    pub value: Option<u64>,
+    pub status: LineStatus,
+}
+
+impl LineReport {
+    pub fn scan(line_number: usize, line: &str, scanner: &Scanner) -> LineReport {
+        let matches = scanner.find_overlapping(line);
+        let Some((first, last)) = ends(&matches) else {
+            return LineReport { line_number, tokens: None, value: None, status: LineStatus::Skipped };
+        };
+        let overlaps = |token: &Match| matches.iter()
+            .any(|other| other != token && other.start < token.end && token.start < other.end);
+        let status = if overlaps(&first) || overlaps(&last) { LineStatus::Ambiguous } else { LineStatus::Ok };
+
+        LineReport {
+            line_number,
+            tokens: Some((Token::new(line, &first), Token::new(line, &last))),
+            value: Some(calibration_value(first.value, last.value)),
+            status,
+        }
+    }
+}
+
+// Everything we decided about each line of a calibration document, so a surprising sum can be
+// traced back to the lines that caused it.
+#[derive(Debug, Default)]
+pub struct CalibrationReport {
+    pub lines: Vec<LineReport>,
+}
+
+impl CalibrationReport {
+    pub fn build(input: impl BufRead, scanner: &Scanner) -> CalibrationReport {
+        let lines = input.lines()
+            .map(|line| line.expect("Couldn't read line"))
+            .enumerate()
+            .map(|(idx, line)| LineReport::scan(idx + 1, &line, scanner))
+            .collect();
+        CalibrationReport { lines }
+    }
+
+    pub fn sum(&self) -> u64 {
+        self.lines.iter().flat_map(|line| line.value).sum()
+    }
+
+    pub fn with_status(&self, status: LineStatus) -> impl Iterator<Item = &LineReport> {
+        self.lines.iter().filter(move |line| line.status == status)
+    }
+
+    pub fn to_csv(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "line,first_token,first_start,first_end,last_token,last_start,last_end,value,status")?;
+        for line in self.lines.iter() {
+            let status = match line.status {
+                LineStatus::Ok => "ok",
+                LineStatus::Ambiguous => "ambiguous",
+                LineStatus::Skipped => "skipped",
+            };
+            match &line.tokens {
+                Some((first, last)) => writeln!(out, "{},{},{},{},{},{},{},{},{status}",
+                    line.line_number,
+                    csv_field(&first.text), first.start, first.end,
+                    csv_field(&last.text), last.start, last.end,
+                    line.value.unwrap())?,
+                None => writeln!(out, "{},,,,,,,,{status}", line.line_number)?,
+            }
+        }
+        Ok(())
+    }
+
+    pub fn write_csv(&self, filename: &str) {
+        let file = File::create(filename)
+            .unwrap_or_else(|e| panic!("Couldn't create report {filename}: {e}"));
+        let mut out = BufWriter::new(file);
+        self.to_csv(&mut out)
+            .and_then(|_| out.flush())
+            .unwrap_or_else(|e| panic!("Couldn't write report {filename}: {e}"));
+    }
+}
+
+// vocabulary words come from a file, so they could have anything in them
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        String::from(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vocabulary::Vocabulary;
+
+    #[test]
+    fn report_lines() {
+        let scanner = Vocabulary::digits().language("english").scanner();
+        let report = CalibrationReport::build("two1nine\nnothing\neightwo\n".as_bytes(), &scanner);
+        assert_eq!(report.sum(), 29 + 82);
+        assert_eq!(report.lines[0].status, LineStatus::Ok);
+        assert_eq!(report.lines[1].status, LineStatus::Skipped);
+        assert_eq!(report.lines[2].status, LineStatus::Ambiguous);
+
+        let (first, last) = report.lines[2].tokens.clone().unwrap();
+        assert_eq!((first.text.as_str(), first.start, first.end), ("eight", 0, 5));
+        assert_eq!((last.text.as_str(), last.start, last.end), ("two", 4, 7));
+
+        let mut csv = vec![];
+        report.to_csv(&mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(),
+                   "line,first_token,first_start,first_end,last_token,last_start,last_end,value,status\n\
+                    1,two,0,3,nine,4,8,29,ok\n\
+                    2,,,,,,,,skipped\n\
+                    3,eight,0,5,two,4,7,82,ambiguous\n");
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(csv_field("un"), "un");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"one\""), "\"say \"\"one\"\"\"");
+    }
+}
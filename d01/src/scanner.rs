@@ -0,0 +1,186 @@
+// An Aho-Corasick automaton: build it once from every word we care about,
+// then a single forward pass over a line finds every (possibly overlapping) match,
+// so "eightwo" gives us both the 8 and the 2.
+
+const NO_STATE: usize = usize::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    // byte offsets into the line, end exclusive
+    pub start: usize,
+    pub end: usize,
+    pub value: u64,
+}
+
+pub struct Scanner {
+    // transitions[state][byte] is the next state; after building, every entry is filled in
+    transitions: Vec<[usize; 256]>,
+    // which patterns end at each state, including ones we reach through failure links
+    outputs: Vec<Vec<usize>>,
+    // (pattern length, value it stands for)
+    patterns: Vec<(usize, u64)>,
+}
+
+impl Scanner {
+    pub fn new<'a>(patterns: impl IntoIterator<Item = (&'a str, u64)>) -> Scanner {
+        Scanner::build(patterns, false)
+    }
+
+    // ASCII letters match either case; anything else has to match exactly
+    pub fn new_ignoring_case<'a>(patterns: impl IntoIterator<Item = (&'a str, u64)>) -> Scanner {
+        Scanner::build(patterns, true)
+    }
+
+    fn build<'a>(patterns: impl IntoIterator<Item = (&'a str, u64)>, ignore_case: bool) -> Scanner {
+        let mut scanner = Scanner {
+            transitions: vec![[NO_STATE; 256]],
+            outputs: vec![vec![]],
+            patterns: vec![],
+        };
+        for (word, value) in patterns {
+            assert!(!word.is_empty(), "Can't scan for an empty word");
+            if ignore_case {
+                scanner.insert(word.to_ascii_lowercase().as_bytes(), value);
+            } else {
+                scanner.insert(word.as_bytes(), value);
+            }
+        }
+        scanner.link();
+        if ignore_case {
+            // every pattern went in lower case, so an upper case letter goes wherever its lower case does
+            for state in scanner.transitions.iter_mut() {
+                for upper in b'A'..=b'Z' {
+                    state[upper as usize] = state[upper.to_ascii_lowercase() as usize];
+                }
+            }
+        }
+        scanner
+    }
+
+    fn insert(&mut self, word: &[u8], value: u64) {
+        let mut state = 0;
+        for &byte in word {
+            if self.transitions[state][byte as usize] == NO_STATE {
+                self.transitions.push([NO_STATE; 256]);
+                self.outputs.push(vec![]);
+                self.transitions[state][byte as usize] = self.transitions.len() - 1;
+            }
+            state = self.transitions[state][byte as usize];
+        }
+        self.outputs[state].push(self.patterns.len());
+        self.patterns.push((word.len(), value));
+    }
+
+    // Walk the trie breadth-first, so each state's failure link (the longest suffix of it that's
+    // also in the trie) is finished before its children need it. Missing transitions are filled in
+    // from the failure link, turning the trie into a DFA with no backtracking at scan time.
+    fn link(&mut self) {
+        let mut fail = vec![0; self.transitions.len()];
+        let mut queue = std::collections::VecDeque::from([0]);
+        while let Some(state) = queue.pop_front() {
+            for byte in 0..256 {
+                let child = self.transitions[state][byte];
+                if child == NO_STATE {
+                    self.transitions[state][byte] = if state == 0 { 0 } else { self.transitions[fail[state]][byte] };
+                } else {
+                    fail[child] = if state == 0 { 0 } else { self.transitions[fail[state]][byte] };
+                    let inherited = self.outputs[fail[child]].clone();
+                    self.outputs[child].extend(inherited);
+                    queue.push_back(child);
+                }
+            }
+        }
+    }
+
+    // every match, in the order they end
+    pub fn find_overlapping(&self, haystack: &str) -> Vec<Match> {
+        let mut matches = vec![];
+        self.each_match(haystack, |m| matches.push(m));
+        matches
+    }
+
+    // the match that starts first and the match that ends last (which might be the same match),
+    // keeping track as it goes so nothing gets collected
+    pub fn first_and_last(&self, haystack: &str) -> Option<(Match, Match)> {
+        let mut ends: Option<(Match, Match)> = None;
+        self.each_match(haystack, |m| ends = Some(match ends {
+            None => (m, m),
+            Some((first, last)) => (earlier(first, m), later(last, m)),
+        }));
+        ends
+    }
+
+    fn each_match(&self, haystack: &str, mut found: impl FnMut(Match)) {
+        let mut state = 0;
+        for (idx, byte) in haystack.bytes().enumerate() {
+            state = self.transitions[state][byte as usize];
+            for &pattern in self.outputs[state].iter() {
+                let (length, value) = self.patterns[pattern];
+                found(Match { start: idx + 1 - length, end: idx + 1, value });
+            }
+        }
+    }
+}
+
+// first_and_last for matches that have already been found
+pub fn ends(matches: &[Match]) -> Option<(Match, Match)> {
+    let first = matches.iter().copied().reduce(earlier)?;
+    let last = matches.iter().copied().reduce(later)?;
+    Some((first, last))
+}
+
+// on a tie, prefer the longer word
+fn earlier(a: Match, b: Match) -> Match {
+    if (b.start, usize::MAX - b.end) < (a.start, usize::MAX - a.end) { b } else { a }
+}
+
+fn later(a: Match, b: Match) -> Match {
+    if (b.end, usize::MAX - b.start) > (a.end, usize::MAX - a.start) { b } else { a }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english() -> Scanner {
+        let words = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+        Scanner::new(["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"].into_iter().zip(0..)
+            .chain(words.into_iter().zip(1..)))
+    }
+
+    #[test]
+    fn overlapping_words() {
+        let scanner = english();
+        let values: Vec<u64> = scanner.find_overlapping("eightwo").iter().map(|m| m.value).collect();
+        assert_eq!(values, vec![8, 2]);
+
+        let matches = scanner.find_overlapping("xtwone3four");
+        assert_eq!(matches, vec![
+            Match { start: 1, end: 4, value: 2 },
+            Match { start: 3, end: 6, value: 1 },
+            Match { start: 6, end: 7, value: 3 },
+            Match { start: 7, end: 11, value: 4 },
+        ]);
+    }
+
+    #[test]
+    fn first_and_last() {
+        let scanner = english();
+        let (first, last) = scanner.first_and_last("zoneight234").unwrap();
+        assert_eq!((first.value, last.value), (1, 4));
+
+        let (first, last) = scanner.first_and_last("treb7uchet").unwrap();
+        assert_eq!((first.value, last.value), (7, 7));
+
+        assert_eq!(scanner.first_and_last("nothing here"), None);
+        assert_eq!(Scanner::new([("1", 1)]).first_and_last("eightwo"), None);
+    }
+
+    #[test]
+    fn nested_words() {
+        // a pattern hiding inside a longer one is still reported, via the failure links
+        let scanner = Scanner::new([("eighteen", 18), ("teen", 10), ("een", 0)]);
+        let values: Vec<u64> = scanner.find_overlapping("eighteen").iter().map(|m| m.value).collect();
+        assert_eq!(values, vec![18, 10, 0]);
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::fs;
+use libaoc::config::Config;
+use crate::scanner::Scanner;
+
+// The words that count as numbers on a calibration line, on top of the digits themselves.
+//
+// A word can stand for more than one digit ("ten" = 10): the first word on a line gives its
+// first digit and the last word gives its last digit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vocabulary {
+    words: HashMap<String, u64>,
+    ignore_case: bool,
+}
+
+const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+const LANGUAGES: [(&str, [&str; 9]); 4] = [
+    ("english", ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"]),
+    ("french", ["un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf"]),
+    ("german", ["eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun"]),
+    ("spanish", ["uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve"]),
+];
+
+impl Vocabulary {
+    // just the digits, for part 1
+    pub fn digits() -> Vocabulary {
+        let mut vocabulary = Vocabulary { words: HashMap::new(), ignore_case: false };
+        for (digit, value) in DIGITS.iter().zip(0..) {
+            vocabulary = vocabulary.word(digit, value);
+        }
+        vocabulary
+    }
+
+    // one..nine in a built-in language
+    pub fn language(self, name: &str) -> Vocabulary {
+        let (_, words) = LANGUAGES.iter()
+            .find(|(language, _)| *language == name)
+            .unwrap_or_else(|| panic!("Unknown language {name}"));
+        words.iter().zip(1..).fold(self, |vocabulary, (word, value)| vocabulary.word(word, value))
+    }
+
+    pub fn word(mut self, word: &str, value: u64) -> Vocabulary {
+        let word = word.trim();
+        assert!(!word.is_empty(), "Vocabulary words can't be empty");
+        if let Some(existing) = self.words.insert(String::from(word), value) {
+            assert_eq!(existing, value, "{word} can't be both {existing} and {value}");
+        }
+        self
+    }
+
+    pub fn ignore_case(mut self, ignore_case: bool) -> Vocabulary {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    // one `word = value` per line, blank lines and `#` comments ignored
+    pub fn read_file(self, filename: &str) -> Vocabulary {
+        let contents = fs::read_to_string(filename)
+            .unwrap_or_else(|e| panic!("Couldn't read vocabulary {filename}: {e}"));
+        self.parse(&contents)
+    }
+
+    pub fn parse(self, contents: &str) -> Vocabulary {
+        contents.lines()
+            .map(|line| line.split('#').next().unwrap().trim())
+            .filter(|line| !line.is_empty())
+            .fold(self, |vocabulary, line| {
+                let (word, value) = line.split_once('=')
+                    .unwrap_or_else(|| panic!("Vocabulary lines should look like `word = 1`, not {line}"));
+                let value = value.trim().parse()
+                    .unwrap_or_else(|_| panic!("{} isn't a number in vocabulary line {line}", value.trim()));
+                vocabulary.word(word, value)
+            })
+    }
+
+    // Part 2's words: `--languages=english,french` (english by default),
+    // plus anything in `--vocabulary=file`, and `--ignore_case` to match them in any case.
+    pub fn from_config(config: &Config) -> Vocabulary {
+        let mut vocabulary = config.get_list("languages", vec![String::from("english")]).iter()
+            .fold(Vocabulary::digits(), |vocabulary, language| vocabulary.language(language));
+        let filename: String = config.get("vocabulary", String::new());
+        if !filename.is_empty() {
+            vocabulary = vocabulary.read_file(&filename);
+        }
+        vocabulary.ignore_case(config.flag("ignore_case"))
+    }
+
+    pub fn scanner(&self) -> Scanner {
+        if self.ignore_case {
+            // ASCII case is handled by the scanner; spell out the other letters in both cases
+            let cased: HashMap<String, u64> = self.words.iter()
+                .flat_map(|(word, value)| [word.to_lowercase(), word.to_uppercase()]
+                    .map(|cased| (cased.to_ascii_lowercase(), *value)))
+                .collect();
+            Scanner::new_ignoring_case(cased.iter().map(|(word, value)| (word.as_str(), *value)))
+        } else {
+            Scanner::new(self.words.iter().map(|(word, value)| (word.as_str(), *value)))
+        }
+    }
+}
+
+// the first digit of the first value and the last digit of the last value
+pub fn calibration_value(first: u64, last: u64) -> u64 {
+    let leading = first.to_string().chars().next().unwrap().to_digit(10).unwrap() as u64;
+    leading * 10 + last % 10
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(vocabulary: &Vocabulary, line: &str) -> Option<u64> {
+        let (first, last) = vocabulary.scanner().first_and_last(line)?;
+        Some(calibration_value(first.value, last.value))
+    }
+
+    #[test]
+    fn languages() {
+        let french = Vocabulary::digits().language("french");
+        assert_eq!(value(&french, "xdeuxunseptz"), Some(27));
+        assert_eq!(value(&french, "eightwo"), None);
+
+        let german = Vocabulary::digits().language("german");
+        assert_eq!(value(&german, "fünfzig3"), Some(53));
+    }
+
+    #[test]
+    fn from_file_contents() {
+        let vocabulary = Vocabulary::digits()
+            .parse("# extras\nzero = 0\nten = 10 # two digits\n")
+            .ignore_case(true);
+        assert_eq!(value(&vocabulary, "TEN"), Some(10));
+        assert_eq!(value(&vocabulary, "zero4Ten"), Some(0));
+        assert_eq!(value(&vocabulary, "9tEn"), Some(90));
+    }
+
+    #[test]
+    fn ignoring_case() {
+        let german = Vocabulary::digits().language("german").ignore_case(true);
+        assert_eq!(value(&german, "FÜNF1"), Some(51));
+        assert_eq!(value(&german, "Sieben"), Some(77));
+        assert_eq!(value(&Vocabulary::digits().language("german"), "Sieben"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "six can't be both 6 and 7")]
+    fn conflicting_words() {
+        Vocabulary::digits().language("english").word("six", 7);
+    }
+}
@@ -0,0 +1,103 @@
+use std::cmp::max;
+use std::collections::BTreeMap;
+use std::fmt;
+
+// A handful of cubes, counted by color. Used both for what came out of a bag and for the bag itself.
+// Any color name works; a color that isn't there counts as zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cubes {
+    counts: BTreeMap<String, u32>,
+}
+
+impl Cubes {
+    // "3 blue, 4 red", the way pulls are written in the input
+    pub fn parse(text: &str) -> Cubes {
+        let mut cubes = Cubes::default();
+        text.split(',')
+            .map(|pair| pair.trim())
+            .filter(|pair| !pair.is_empty())
+            .for_each(|pair| {
+                let (count, color) = pair.split_once(' ')
+                    .unwrap_or_else(|| panic!("Expected a count and a color, got: {pair}"));
+                let count: u32 = count.parse()
+                    .unwrap_or_else(|_| panic!("Expected a count, got: {count}"));
+                cubes.add(color.trim(), count);
+            });
+        cubes
+    }
+
+    pub fn add(&mut self, color: &str, count: u32) {
+        *self.counts.entry(String::from(color)).or_insert(0) += count;
+    }
+
+    // take away up to `count` of a color
+    pub fn remove(&mut self, color: &str, count: u32) {
+        if let Some(have) = self.counts.get_mut(color) {
+            *have = have.saturating_sub(count);
+        }
+    }
+
+    // how many cubes there are, whatever their colors
+    pub fn total(&self) -> u32 {
+        self.counts.values().sum()
+    }
+
+    pub fn get(&self, color: &str) -> u32 {
+        self.counts.get(color).copied().unwrap_or(0)
+    }
+
+    pub fn colors(&self) -> impl Iterator<Item = &str> {
+        self.counts.keys().map(String::as_str)
+    }
+
+    // could these all have come out of the bag at once?
+    pub fn fits_in(&self, bag: &Cubes) -> bool {
+        self.counts.iter().all(|(color, count)| *count <= bag.get(color))
+    }
+
+    // the smallest collection that has at least as many of each color as either side
+    pub fn union(&self, other: &Cubes) -> Cubes {
+        let mut union = self.clone();
+        for (color, count) in other.counts.iter() {
+            let entry = union.counts.entry(color.clone()).or_insert(0);
+            *entry = max(*entry, *count);
+        }
+        union
+    }
+
+    // multiply the counts of the given colors together, with missing colors counting as zero
+    pub fn power<'a>(&self, palette: impl IntoIterator<Item = &'a str>) -> u32 {
+        palette.into_iter().map(|color| self.get(color)).product()
+    }
+}
+
+impl fmt::Display for Cubes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs: Vec<String> = self.counts.iter().map(|(color, count)| format!("{count} {color}")).collect();
+        write!(f, "{}", pairs.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_and_fitting() {
+        let bag = Cubes::parse("12 red, 13 green, 14 blue");
+        assert_eq!(bag.get("green"), 13);
+        assert_eq!(bag.get("purple"), 0);
+        assert!(Cubes::parse("3 blue, 4 red").fits_in(&bag));
+        assert!(!Cubes::parse("20 red").fits_in(&bag));
+        assert!(!Cubes::parse("1 purple").fits_in(&bag));
+        assert_eq!(Cubes::parse("2 purple,3 teal").to_string(), "2 purple, 3 teal");
+    }
+
+    #[test]
+    fn union_and_power() {
+        let union = Cubes::parse("3 blue, 4 red").union(&Cubes::parse("1 red, 2 green, 6 blue"));
+        assert_eq!(union, Cubes::parse("4 red, 2 green, 6 blue"));
+        assert_eq!(union.power(["red", "green", "blue"]), 48);
+        assert_eq!(union.power(["red", "purple"]), 0);
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use crate::cubes::Cubes;
+use crate::{minimal_bag, Game};
+
+// Treat every game as a set of observations of the same bag, and work backwards to the bag.
+pub struct Inference {
+    // the smallest bag that could have produced every game
+    pub bag: Cubes,
+    // for each color, the games that pulled out that color's full count, so they're what sets it
+    pub binding: BTreeMap<String, Vec<u32>>,
+}
+
+impl Inference {
+    pub fn new(games: &[Game]) -> Inference {
+        let bag = minimal_bag(games);
+        let binding = bag.colors()
+            .map(|color| {
+                let ids = games.iter()
+                    .filter(|g| g.pulls.iter().any(|p| p.get(color) == bag.get(color)))
+                    .map(|g| g.id)
+                    .collect();
+                (String::from(color), ids)
+            })
+            .collect();
+
+        Inference { bag, binding }
+    }
+}
+
+// How likely a bag is to have produced every pull we saw, assuming each pull grabs that many cubes
+// without replacement (and puts them all back afterwards), so each pull is a multivariate hypergeometric draw:
+//
+//   P(pull) = product over colors of C(in bag, pulled) / C(bag size, pull size)
+//
+// Returned as a natural log, so it's negative infinity for a bag that couldn't have done it.
+pub fn log_likelihood(bag: &Cubes, games: &[Game]) -> f64 {
+    let bag_size: u32 = bag.colors().map(|color| bag.get(color)).sum();
+    games.iter()
+        .flat_map(|g| g.pulls.iter())
+        .map(|pull| {
+            if !pull.fits_in(bag) {
+                return f64::NEG_INFINITY;
+            }
+            let pull_size: u32 = pull.colors().map(|color| pull.get(color)).sum();
+            let ways: f64 = bag.colors().map(|color| ln_choose(bag.get(color), pull.get(color))).sum();
+            ways - ln_choose(bag_size, pull_size)
+        })
+        .sum()
+}
+
+// Start from a bag and keep making whichever single-cube change helps the likelihood most (adding a cube,
+// taking one out, or swapping one for another color) until nothing helps.
+//
+// Bigger bags can keep getting a little likelier without end, as the pulls look more and more like
+// draws with replacement, so there may be no most likely bag of any size: the search only looks at
+// bags of at most max_size cubes. Every change makes the bag strictly likelier and there are only
+// so many bags that small, so it always stops, at a bag no single-cube change improves on.
+pub fn most_likely_bag(games: &[Game], start: &Cubes, max_size: u32) -> Cubes {
+    assert!(start.total() <= max_size, "{start} already has more than {max_size} cubes");
+    let colors: Vec<String> = start.colors().map(String::from).collect();
+    let mut bag = start.clone();
+    let mut best = log_likelihood(&bag, games);
+    loop {
+        let improvement = neighbours(&bag, &colors, max_size).into_iter()
+            .map(|candidate| {
+                let score = log_likelihood(&candidate, games);
+                (candidate, score)
+            })
+            .filter(|(_, score)| *score > best)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+        match improvement {
+            Some((candidate, score)) => {
+                bag = candidate;
+                best = score;
+            },
+            None => return bag
+        }
+    }
+}
+
+// every bag of at most max_size cubes that's one cube away from this one
+fn neighbours(bag: &Cubes, colors: &[String], max_size: u32) -> Vec<Cubes> {
+    let mut candidates = vec![];
+    for color in colors {
+        if bag.total() < max_size {
+            let mut added = bag.clone();
+            added.add(color, 1);
+            candidates.push(added);
+        }
+        if bag.get(color) == 0 {
+            continue;
+        }
+        let mut taken = bag.clone();
+        taken.remove(color, 1);
+        for other in colors.iter().filter(|other| *other != color) {
+            let mut swapped = taken.clone();
+            swapped.add(other, 1);
+            candidates.push(swapped);
+        }
+        candidates.push(taken);
+    }
+    candidates
+}
+
+fn ln_choose(n: u32, k: u32) -> f64 {
+    if k > n {
+        return f64::NEG_INFINITY;
+    }
+    let k = k.min(n - k);
+    (1..=k).map(|i| ((n - k + i) as f64 / i as f64).ln()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::line_to_game;
+
+    #[test]
+    fn binding_games() {
+        let games: Vec<Game> = ["Game 1: 3 blue, 4 red; 2 green", "Game 2: 4 blue; 1 red, 2 green"]
+            .into_iter().map(line_to_game).collect();
+        let inference = Inference::new(&games);
+        assert_eq!(inference.bag, Cubes::parse("4 red, 2 green, 4 blue"));
+        assert_eq!(inference.binding["red"], vec![1]);
+        assert_eq!(inference.binding["green"], vec![1, 2]);
+        assert_eq!(inference.binding["blue"], vec![2]);
+    }
+
+    #[test]
+    fn likelihoods() {
+        assert!((ln_choose(5, 2) - 10f64.ln()).abs() < 1e-9);
+        assert_eq!(ln_choose(2, 3), f64::NEG_INFINITY);
+
+        // pulling 1 red out of a bag of 1 red and 1 blue happens half the time
+        let games = vec![line_to_game("Game 1: 1 red")];
+        let bag = Cubes::parse("1 red, 1 blue");
+        assert!((log_likelihood(&bag, &games) - 0.5f64.ln()).abs() < 1e-9);
+        assert_eq!(log_likelihood(&Cubes::parse("1 blue"), &games), f64::NEG_INFINITY);
+
+        // taking the blue out makes a bag that always pulls a red, and nothing beats that
+        assert_eq!(most_likely_bag(&games, &bag, 10), Cubes::parse("1 red, 0 blue"));
+    }
+
+    #[test]
+    fn bounded_search() {
+        // pulling 2 red and then 2 blue gets likelier as the bag grows, as long as the colors stay even,
+        // so the search fills the bag up evenly and stops once it's full
+        let games = vec![line_to_game("Game 1: 2 red; 2 blue")];
+        let start = Cubes::parse("2 red, 2 blue");
+        let best = most_likely_bag(&games, &start, 10);
+        assert_eq!(best, Cubes::parse("5 red, 5 blue"));
+        let colors = [String::from("red"), String::from("blue")];
+        for candidate in neighbours(&best, &colors, 10) {
+            assert!(log_likelihood(&candidate, &games) < log_likelihood(&best, &games), "{candidate} is likelier");
+        }
+        // and with room for more, it finds a likelier bag still
+        let bigger = most_likely_bag(&games, &start, 20);
+        assert_eq!(bigger, Cubes::parse("10 red, 10 blue"));
+    }
+}
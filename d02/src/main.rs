@@ -1,60 +1,114 @@
-use std::cmp::max;
+use std::collections::BTreeSet;
 use std::fs;
+use libaoc::config::Config;
+use libaoc::debug;
+use crate::cubes::Cubes;
+use crate::inference::{log_likelihood, most_likely_bag, Inference};
+
+mod cubes;
+mod inference;
 
 struct Game {
     id: u32,
-    pulls: Vec<Pull>
+    pulls: Vec<Cubes>
 }
 
 impl Game {
-    fn minimal_set(&self) -> Pull {
-        let mut minimal_pull = Pull {red: 0, green: 0, blue: 0};
-        self.pulls.iter().for_each(|p| {
-            minimal_pull.red = max(minimal_pull.red, p.red);
-            minimal_pull.green = max(minimal_pull.green, p.green);
-            minimal_pull.blue = max(minimal_pull.blue, p.blue);
-        });
-
-        minimal_pull
+    fn minimal_set(&self) -> Cubes {
+        minimal_bag(std::slice::from_ref(self))
+    }
+
+    fn possible_with(&self, bag: &Cubes) -> bool {
+        self.pulls.iter().all(|p| p.fits_in(bag))
     }
 }
 
-struct Pull {
-    red: u32,
-    green: u32,
-    blue: u32
+fn possible_games<'a>(games: &'a [Game], bag: &Cubes) -> Vec<&'a Game> {
+    games.iter().filter(|g| g.possible_with(bag)).collect()
+}
+
+// the smallest bag that every one of these games could have been played with
+fn minimal_bag(games: &[Game]) -> Cubes {
+    games.iter()
+        .flat_map(|g| g.pulls.iter())
+        .fold(Cubes::default(), |bag, p| bag.union(p))
 }
 
 fn main() {
-    part1();
+    let config = Config::load();
+    part1(&config);
     part2();
+    if config.flag("infer") {
+        infer(&config);
+    }
 }
 
-fn part1() {
+// `--bag="12 red, 13 green, 14 blue"` asks about any bag; otherwise it's built from --red, --green and --blue
+fn bag_from_config(config: &Config) -> Cubes {
+    let bag: String = config.get("bag", String::new());
+    if !bag.is_empty() {
+        return Cubes::parse(&bag);
+    }
+    let mut bag = Cubes::default();
+    bag.add("red", config.get("red", 12));
+    bag.add("green", config.get("green", 13));
+    bag.add("blue", config.get("blue", 14));
+    bag
+}
+
+fn part1(config: &Config) {
     let games = read_games();
+    let bag = bag_from_config(config);
 
-    let red_threshold = 12;
-    let green_threshold = 13;
-    let blue_threshold = 14;
-    let score: u32 = games.iter()
-        .filter(|g| g.pulls.iter().all(|p| {
-            p.red <= red_threshold && p.green <= green_threshold && p.blue <= blue_threshold
-        }))
-        .map(|g| g.id)
-        .sum();
+    let possible = possible_games(&games, &bag);
+    let ids: Vec<u32> = possible.iter().map(|g| g.id).collect();
+    debug!("Games possible with {bag}: {ids:?}");
+    debug!("Smallest bag for all games: {}", minimal_bag(&games));
+    let score: u32 = ids.iter().sum();
     println!("{score}");
 }
 
 fn part2() {
     let games = read_games();
+    // a game that never shows some color needed none of it, so its power is zero
+    let palette: BTreeSet<String> = games.iter()
+        .flat_map(|g| g.pulls.iter())
+        .flat_map(|p| p.colors().map(String::from))
+        .collect();
     let score: u32 = games.iter()
         .map(|g| g.minimal_set())
-        .map(|p| p.red * p.green * p.blue)
+        .map(|p| p.power(palette.iter().map(String::as_str)))
         .sum();
     println!("{score}");
 
 }
 
+// `--infer` works out which bag the games were most likely played with.
+// `--candidates="12 red, 13 green, 14 blue; 20 red, 13 green, 15 blue"` scores some bags of your own too,
+// and `--max_size` is the most cubes the search will put in a bag (100).
+fn infer(config: &Config) {
+    let games = read_games();
+    let inference = Inference::new(&games);
+    println!("Smallest bag for every game: {}", inference.bag);
+    for (color, ids) in inference.binding.iter() {
+        println!("  {color} is set by games {ids:?}");
+    }
+
+    let mut candidates = vec![inference.bag.clone()];
+    let extra: String = config.get("candidates", String::new());
+    candidates.extend(extra.split(';').filter(|c| !c.trim().is_empty()).map(Cubes::parse));
+    for bag in candidates.iter() {
+        println!("Log likelihood of {bag}: {:.3}", log_likelihood(bag, &games));
+    }
+    let max_size = config.get("max_size", 100);
+    if inference.bag.total() > max_size {
+        println!("The smallest bag already has more than {max_size} cubes, so there's nothing to search");
+        return;
+    }
+    let best = most_likely_bag(&games, &inference.bag, max_size);
+    println!("Most likely bag of at most {max_size} cubes: {best} ({:.3})", log_likelihood(&best, &games));
+}
+
 fn read_games() -> Vec<Game> {
     let games: Vec<Game> = fs::read_to_string("input")
         .unwrap()
@@ -70,9 +124,9 @@ fn line_to_game(line: &str) -> Game {
         .split(" ")
         .last().unwrap()
         .parse().unwrap();
-    let pulls: Vec<Pull> = parts[1]
+    let pulls: Vec<Cubes> = parts[1]
         .split("; ")
-        .map(parse_pull)
+        .map(Cubes::parse)
         .collect();
 
     return Game {
@@ -81,24 +135,31 @@ fn line_to_game(line: &str) -> Game {
     }
 }
 
-fn parse_pull(pull_str: &str) -> Pull {
-    let mut pull = Pull {
-        red: 0,
-        green: 0,
-        blue: 0
-    };
-
-    pull_str.split(", ")
-        .for_each(|pair| {
-            let parts: Vec<&str> = pair.split(" ").collect();
-            let count: u32 = parts[0].parse().unwrap();
-            match parts[1] {
-                "red" => pull.red = count,
-                "green" => pull.green = count,
-                "blue" => pull.blue = count,
-                _ => panic!("Got unexpected color: {}", parts[1])
-            }
-        });
-
-    return pull;
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\n\
+                           Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\n\
+                           Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\n\
+                           Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\n\
+                           Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn bag_queries() {
+        let games: Vec<Game> = EXAMPLE.lines().map(line_to_game).collect();
+        let bag = Cubes::parse("12 red, 13 green, 14 blue");
+        let ids: Vec<u32> = possible_games(&games, &bag).iter().map(|g| g.id).collect();
+        assert_eq!(ids, vec![1, 2, 5]);
+        assert_eq!(games[0].minimal_set(), Cubes::parse("4 red, 2 green, 6 blue"));
+        assert_eq!(minimal_bag(&games), Cubes::parse("20 red, 13 green, 15 blue"));
+        assert!(games.iter().all(|g| g.possible_with(&minimal_bag(&games))));
+    }
+
+    #[test]
+    fn other_colors() {
+        let game = line_to_game("Game 7: 2 purple, 1 red; 5 teal");
+        assert!(game.possible_with(&Cubes::parse("2 purple, 1 red, 5 teal")));
+        assert!(!game.possible_with(&Cubes::parse("12 red, 13 green, 14 blue")));
+    }
+}
@@ -1,100 +1,34 @@
-use std::cmp::max;
 use std::fs;
-use regex::Regex;
-use lazy_static::lazy_static;
-
-lazy_static!{
-pub static ref NUMBER: Regex = Regex::new(r"\d+").unwrap();
-pub static ref SYMBOL: Regex = Regex::new(r"[^.\d]").unwrap();
-}
-
-#[derive(PartialEq, Eq, Hash, Debug)]
-struct Label {
-    number: u32,
-    y: i32,
-    xrange: Range,
-}
-
-impl Label {
-    fn adjacent_to(&self, symbol: &Symbol) -> bool {
-        symbol.yrange().contains(self.y) &&
-            symbol.xrange().overlaps(&self.xrange)
-    }
-}
-
-#[derive(PartialEq, Eq, Hash, Debug)]
-struct Symbol {
-    value: String,
-    x: i32,
-    y: i32
-}
-
-impl Symbol {
-    fn xrange(&self) -> Range {
-        Range {
-            begin: max(0, self.x - 1),
-            end: self.x + 1
-        }
-    }
-
-    fn yrange(&self) -> Range {
-        Range {
-            begin: max(0, self.y - 1),
-            end: self.y + 1
-        }
-    }
-}
-
-#[derive(PartialEq, Eq, Hash, Debug)]
-struct Range {
-    begin: i32,
-    end: i32,
-}
-
-impl Range {
-    fn overlaps(&self, other: &Range) -> bool {
-        return (self.begin >= other.begin && self.begin <= other.end) ||
-            (other.begin >= self.begin && other.begin <= self.end);
-    }
-
-    fn contains(&self, value: i32) -> bool {
-        return self.begin <= value && self.end >= value;
-    }
-}
+use std::ops::RangeInclusive;
+use libaoc::config::Config;
+use libaoc::{debug, trace};
+use crate::schematic::Schematic;
 
+mod render;
+mod schematic;
 
 fn main() {
-    part1();
-    part2();
+    let config = Config::load();
+    let schematic = Schematic::read("input");
+    debug!("Got {} labels and {} symbols on {} lines",
+           schematic.labels.len(), schematic.symbols.len(), schematic.lines.len());
+    match config.positional().first().map(String::as_str) {
+        Some("gears") => return gears(&config, &schematic),
+        Some("render") => return render(&config, &schematic),
+        _ => {}
+    }
+    part1(&schematic);
+    part2(&schematic);
 }
 
-fn debug() {
-    let (labels, symbols) = parse_map("example");
-    let matching_labels: Vec<&Label> = labels.iter()
+fn part1(schematic: &Schematic) {
+    let matches: Vec<u32> = schematic.labels.iter()
         .filter(|label| {
-            symbols.iter().any(|symbol| {
-                label.adjacent_to(symbol)
-            })
-        })
-        .collect();
-    let label_20 = labels.iter().find(|l| l.number == 20).unwrap();
-    let matches_y_0 = symbols[0].yrange().contains(label_20.y);
-    let matches_x_0 = symbols[0].xrange().overlaps(&label_20.xrange);
-    let matches_y_1 = symbols[1].yrange().contains(label_20.y);
-    let matches_x_1 = symbols[1].xrange().overlaps(&label_20.xrange);
-    let symbol_1_yr = symbols[1].yrange();
-    println!("Done!");
-}
-
-fn part1() {
-    let (labels, symbols) = parse_map("input");
-    println!("Got {} labels and {} symbols", labels.len(), symbols.len());
-
-    let matches: Vec<u32> = labels.iter()
-        .filter(|label| {
-            symbols.iter().any(|symbol| {
-                label.adjacent_to(symbol)
-            })
+            let is_part = schematic.is_part(label);
+            if !is_part {
+                trace!("{} on line {} isn't next to any symbol", label.number, label.y);
+            }
+            is_part
         })
         .map(|label| label.number)
         .collect();
@@ -104,57 +38,76 @@ fn part1() {
     println!("{score}");
 }
 
-fn part2() {
-    let (labels, symbols) = parse_map("input");
-    let score: u32 = symbols
+fn part2(schematic: &Schematic) {
+    let score: u64 = schematic.gears("*", 2..=2, |numbers| combine("product", numbers))
         .iter()
-        .filter(|s| s.value == "*")
-        .map(|symbol| {
-            let labels: Vec<&Label> = labels.iter()
-                .filter(|label| label.adjacent_to(symbol))
-                .collect();
-            labels
-        })
-        .filter(|labels| labels.len() == 2)
-        .map(|labels| {
-            let product: u32 = labels.iter()
-                .map(|label| label.number)
-                .product();
-            product
-        })
+        .map(|(_, ratio)| ratio)
         .sum();
     println!("{score}");
 }
 
-fn parse_map(filename: &str) -> (Vec<Label>, Vec<Symbol>) {
-    let file = fs::read_to_string(filename)
-        .unwrap();
+// `d03 gears --symbols=# --count=3 --combine=product --total=sum`
+//
+//   --symbols  which symbols count as gears (*)
+//   --count    how many numbers they have to touch, either `2` or a range like `2-4` (2)
+//   --combine  what to make of each gear's numbers: product, sum, max, min or count (product)
+//   --total    how to total up the gears: sum, max or min (sum)
+fn gears(config: &Config, schematic: &Schematic) {
+    let symbols: String = config.get("symbols", String::from("*"));
+    let count = parse_count(&config.get("count", String::from("2")));
+    let combine_by: String = config.get("combine", String::from("product"));
+    let total_by: String = config.get("total", String::from("sum"));
+
+    let gears = schematic.gears(&symbols, count, |numbers| combine(&combine_by, numbers));
+    for (symbol, value) in gears.iter() {
+        debug!("{} at {},{} => {value}", symbol.value, symbol.x, symbol.y);
+    }
+    let values = gears.iter().map(|(_, value)| *value);
+    let total = match total_by.as_str() {
+        "sum" => Some(values.sum()),
+        "max" => values.max(),
+        "min" => values.min(),
+        other => panic!("Unknown total {other}, expected sum, max or min")
+    };
+    match total {
+        Some(total) => println!("{total_by} over {} gears: {total}", gears.len()),
+        None => println!("No gears matched"),
+    }
 
-    let labels: Vec<Label> = file
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            NUMBER.find_iter(line)
-                .map(move |m| Label {
-                    number: m.as_str().parse().unwrap(),
-                    y: y as i32,
-                    xrange: Range {begin: m.start() as i32, end: (m.end() - 1) as i32 }
-                })
-        })
+    let unattached: Vec<String> = schematic.unattached().iter()
+        .map(|label| format!("{} (line {})", label.number, label.y + 1))
         .collect();
+    println!("Numbers next to no symbol: {}", unattached.join(", "));
+}
 
-    let symbols: Vec<Symbol> = file
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            SYMBOL.find_iter(line)
-                .map(move |m| Symbol {
-                    value: String::from(m.as_str()),
-                    y: y as i32,
-                    x: m.start() as i32
-                })
-        })
-        .collect();
+// `d03 render` draws the schematic in the terminal; `--html=path` writes a web page instead
+fn render(config: &Config, schematic: &Schematic) {
+    let html: String = config.get("html", String::new());
+    if html.is_empty() {
+        print!("{}", render::to_ansi(schematic));
+    } else {
+        fs::write(&html, render::to_html(schematic))
+            .unwrap_or_else(|e| panic!("Couldn't write {html}: {e}"));
+    }
+}
 
-    return (labels, symbols)
+fn parse_count(count: &str) -> RangeInclusive<usize> {
+    let parse = |n: &str| n.trim().parse::<usize>()
+        .unwrap_or_else(|_| panic!("--count should look like 2 or 2-4, not {count}"));
+    match count.split_once('-') {
+        Some((low, high)) => parse(low)..=parse(high),
+        None => parse(count)..=parse(count),
+    }
+}
+
+fn combine(how: &str, numbers: &[u32]) -> u64 {
+    let numbers = numbers.iter().map(|n| *n as u64);
+    match how {
+        "product" => numbers.product(),
+        "sum" => numbers.sum(),
+        "max" => numbers.max().unwrap_or(0),
+        "min" => numbers.min().unwrap_or(0),
+        "count" => numbers.count() as u64,
+        other => panic!("Unknown combine {other}, expected product, sum, max, min or count")
+    }
 }
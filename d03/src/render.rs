@@ -0,0 +1,133 @@
+use crate::schematic::{Label, Schematic, Symbol};
+
+// What each number or symbol is, for coloring it in.
+#[derive(Debug, PartialEq)]
+enum Kind {
+    Part,
+    NotPart,
+    // a * touching exactly two numbers, with their product
+    Gear(u64),
+    Symbol,
+    Blank,
+}
+
+fn kind_of_label(schematic: &Schematic, label: &Label) -> Kind {
+    if schematic.is_part(label) { Kind::Part } else { Kind::NotPart }
+}
+
+fn kind_of_symbol(schematic: &Schematic, symbol: &Symbol) -> Kind {
+    let numbers = schematic.labels_touching(symbol);
+    if symbol.value == "*" && numbers.len() == 2 {
+        Kind::Gear(numbers.iter().map(|label| label.number as u64).product())
+    } else {
+        Kind::Symbol
+    }
+}
+
+// The cells of one line, with each number's digits kept together.
+fn segments(schematic: &Schematic, y: i32) -> Vec<(String, Kind, Option<&Label>)> {
+    let line = &schematic.lines[y as usize];
+    let mut segments = vec![];
+    let mut x = 0;
+    while (x as usize) < line.len() {
+        if let Some(label) = schematic.label_at(x, y) {
+            let text = String::from(&line[label.xrange.begin as usize..=label.xrange.end as usize]);
+            segments.push((text, kind_of_label(schematic, label), Some(label)));
+            x = label.xrange.end + 1;
+            continue;
+        }
+        let ch = line[x as usize..].chars().next().unwrap();
+        let kind = match schematic.symbol_at(x, y) {
+            Some(symbol) => kind_of_symbol(schematic, symbol),
+            None => Kind::Blank,
+        };
+        segments.push((String::from(ch), kind, None));
+        x += ch.len_utf8() as i32;
+    }
+    segments
+}
+
+// Part numbers in green, numbers next to no symbol in red, gears in yellow with their ratios
+// listed at the end of the line.
+pub fn to_ansi(schematic: &Schematic) -> String {
+    let mut text = String::new();
+    for y in 0..schematic.lines.len() as i32 {
+        let mut ratios = vec![];
+        for (segment, kind, _) in segments(schematic, y) {
+            let color = match kind {
+                Kind::Part => "32",
+                Kind::NotPart => "1;31",
+                Kind::Gear(ratio) => {
+                    ratios.push(ratio.to_string());
+                    "1;30;43"
+                },
+                Kind::Symbol => "36",
+                Kind::Blank => "2",
+            };
+            text.push_str(&format!("\x1b[{color}m{segment}\x1b[0m"));
+        }
+        if !ratios.is_empty() {
+            text.push_str(&format!("  gear ratio {}", ratios.join(", ")));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+// The same thing as a web page, where hovering over a number or gear says why it's colored that way.
+pub fn to_html(schematic: &Schematic) -> String {
+    let mut html = String::from("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><style>\n\
+        body { background: #111; color: #555; font-family: monospace; }\n\
+        .part { color: #5b5; } .not-part { color: #e44; font-weight: bold; }\n\
+        .gear { background: #eb3; color: #111; } .symbol { color: #4bd; }\n\
+        </style></head><body><pre>\n");
+    for y in 0..schematic.lines.len() as i32 {
+        for (segment, kind, label) in segments(schematic, y) {
+            let segment = escape(&segment);
+            match (kind, label) {
+                (Kind::Blank, _) => html.push_str(&segment),
+                (Kind::Symbol, _) => html.push_str(&format!("<span class=\"symbol\">{segment}</span>")),
+                (Kind::Gear(ratio), _) => html.push_str(
+                    &format!("<span class=\"gear\" title=\"gear ratio {ratio}\">{segment}</span>")),
+                (kind, Some(label)) => {
+                    let touching: Vec<String> = schematic.symbols_touching(label).iter()
+                        .map(|s| format!("{} on line {}", escape(&s.value), s.y + 1))
+                        .collect();
+                    let (class, title) = if kind == Kind::Part {
+                        ("part", format!("{}: next to {}", label.number, touching.join(", ")))
+                    } else {
+                        ("not-part", format!("{}: not next to any symbol", label.number))
+                    };
+                    html.push_str(&format!("<span class=\"{class}\" title=\"{title}\">{segment}</span>"));
+                },
+                (_, None) => unreachable!("only numbers are parts"),
+            }
+        }
+        html.push('\n');
+    }
+    html.push_str("</pre></body></html>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rendering() {
+        let schematic = Schematic::parse("467..114..\n...*......\n..35..&...");
+        let ansi = to_ansi(&schematic);
+        assert!(ansi.contains("\x1b[32m467\x1b[0m"));
+        assert!(ansi.contains("\x1b[1;31m114\x1b[0m"));
+        assert!(ansi.lines().nth(1).unwrap().ends_with("gear ratio 16345"));
+
+        let html = to_html(&schematic);
+        assert!(html.contains("<span class=\"gear\" title=\"gear ratio 16345\">*</span>"));
+        assert!(html.contains("<span class=\"part\" title=\"35: next to * on line 2\">35</span>"));
+        assert!(html.contains("<span class=\"symbol\">&amp;</span>"));
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::fs;
+use std::ops::RangeInclusive;
+use regex::Regex;
+use lazy_static::lazy_static;
+
+lazy_static!{
+pub static ref NUMBER: Regex = Regex::new(r"\d+").unwrap();
+pub static ref SYMBOL: Regex = Regex::new(r"[^.\d]").unwrap();
+}
+
+#[derive(PartialEq, Eq, Hash, Debug)]
+pub struct Label {
+    pub number: u32,
+    pub y: i32,
+    pub xrange: Range,
+}
+
+#[derive(PartialEq, Eq, Hash, Debug)]
+pub struct Symbol {
+    pub value: String,
+    pub x: i32,
+    pub y: i32
+}
+
+// inclusive on both ends
+#[derive(PartialEq, Eq, Hash, Debug)]
+pub struct Range {
+    pub begin: i32,
+    pub end: i32,
+}
+
+// The whole engine schematic, parsed once. Every cell a number covers and every symbol's cell
+// is indexed, so finding what touches what only looks at the neighboring cells.
+pub struct Schematic {
+    pub lines: Vec<String>,
+    pub labels: Vec<Label>,
+    pub symbols: Vec<Symbol>,
+    // (x, y) => index into labels/symbols
+    label_at: HashMap<(i32, i32), usize>,
+    symbol_at: HashMap<(i32, i32), usize>,
+}
+
+impl Schematic {
+    pub fn read(filename: &str) -> Schematic {
+        let file = fs::read_to_string(filename)
+            .unwrap();
+        Schematic::parse(&file)
+    }
+
+    pub fn parse(file: &str) -> Schematic {
+        let lines: Vec<String> = file.lines().map(String::from).collect();
+
+        let labels: Vec<Label> = lines.iter()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                NUMBER.find_iter(line)
+                    .map(move |m| Label {
+                        number: m.as_str().parse().unwrap(),
+                        y: y as i32,
+                        xrange: Range {begin: m.start() as i32, end: (m.end() - 1) as i32 }
+                    })
+            })
+            .collect();
+
+        let symbols: Vec<Symbol> = lines.iter()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                SYMBOL.find_iter(line)
+                    .map(move |m| Symbol {
+                        value: String::from(m.as_str()),
+                        y: y as i32,
+                        x: m.start() as i32
+                    })
+            })
+            .collect();
+
+        let label_at = labels.iter()
+            .enumerate()
+            .flat_map(|(idx, label)| {
+                (label.xrange.begin..=label.xrange.end).map(move |x| ((x, label.y), idx))
+            })
+            .collect();
+        let symbol_at = symbols.iter()
+            .enumerate()
+            .map(|(idx, symbol)| ((symbol.x, symbol.y), idx))
+            .collect();
+
+        Schematic { lines, labels, symbols, label_at, symbol_at }
+    }
+
+    pub fn label_at(&self, x: i32, y: i32) -> Option<&Label> {
+        self.label_at.get(&(x, y)).map(|idx| &self.labels[*idx])
+    }
+
+    pub fn symbol_at(&self, x: i32, y: i32) -> Option<&Symbol> {
+        self.symbol_at.get(&(x, y)).map(|idx| &self.symbols[*idx])
+    }
+
+    // the numbers in any of the 8 cells around the symbol, each once, in reading order
+    pub fn labels_touching(&self, symbol: &Symbol) -> Vec<&Label> {
+        let mut indexes: Vec<usize> = (symbol.y - 1..=symbol.y + 1)
+            .flat_map(|y| (symbol.x - 1..=symbol.x + 1).map(move |x| (x, y)))
+            .flat_map(|pos| self.label_at.get(&pos).copied())
+            .collect();
+        indexes.sort();
+        indexes.dedup();
+        indexes.into_iter().map(|idx| &self.labels[idx]).collect()
+    }
+
+    // the symbols in the ring of cells around the number, in reading order
+    pub fn symbols_touching(&self, label: &Label) -> Vec<&Symbol> {
+        let mut indexes: Vec<usize> = (label.y - 1..=label.y + 1)
+            .flat_map(|y| (label.xrange.begin - 1..=label.xrange.end + 1).map(move |x| (x, y)))
+            .flat_map(|pos| self.symbol_at.get(&pos).copied())
+            .collect();
+        indexes.sort();
+        indexes.into_iter().map(|idx| &self.symbols[idx]).collect()
+    }
+
+    pub fn is_part(&self, label: &Label) -> bool {
+        !self.symbols_touching(label).is_empty()
+    }
+
+    // numbers that aren't next to any symbol at all
+    pub fn unattached(&self) -> Vec<&Label> {
+        self.labels.iter().filter(|label| !self.is_part(label)).collect()
+    }
+
+    // Every symbol that's one of `symbols` and touches `count` numbers, along with whatever `combine`
+    // makes of those numbers. Part 2 is gears("*", 2..=2, product).
+    pub fn gears(&self, symbols: &str, count: RangeInclusive<usize>, combine: impl Fn(&[u32]) -> u64)
+        -> Vec<(&Symbol, u64)> {
+        self.symbols.iter()
+            .filter(|symbol| symbols.contains(symbol.value.as_str()))
+            .flat_map(|symbol| {
+                let numbers: Vec<u32> = self.labels_touching(symbol).iter().map(|label| label.number).collect();
+                if count.contains(&numbers.len()) {
+                    Some((symbol, combine(&numbers)))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "467..114..\n...*......\n..35..633.\n......#...\n617*......\n\
+                         .....+.58.\n..592.....\n......755.\n...$.*....\n.664.598..";
+
+    #[test]
+    fn adjacency() {
+        let schematic = Schematic::parse(EXAMPLE);
+        assert_eq!(schematic.labels.len(), 10);
+        assert_eq!(schematic.symbols.len(), 6);
+
+        let star = &schematic.symbols[0];
+        let numbers: Vec<u32> = schematic.labels_touching(star).iter().map(|l| l.number).collect();
+        assert_eq!(numbers, vec![467, 35]);
+
+        let parts: Vec<u32> = schematic.labels.iter()
+            .filter(|l| !schematic.is_part(l))
+            .map(|l| l.number)
+            .collect();
+        assert_eq!(parts, vec![114, 58]);
+
+        let symbols: Vec<&str> = schematic.symbols_touching(&schematic.labels[4]).iter()
+            .map(|s| s.value.as_str())
+            .collect();
+        assert_eq!(symbols, vec!["*"]);
+    }
+
+    #[test]
+    fn gear_queries() {
+        let schematic = Schematic::parse(EXAMPLE);
+        let product = |numbers: &[u32]| numbers.iter().map(|n| *n as u64).product();
+        let ratios: Vec<u64> = schematic.gears("*", 2..=2, product).iter().map(|(_, r)| *r).collect();
+        assert_eq!(ratios, vec![16345, 451490]);
+
+        // the lone * next to 617 only counts when we ask for one number
+        let sum = |numbers: &[u32]| numbers.iter().map(|n| *n as u64).sum();
+        let sums: Vec<(i32, u64)> = schematic.gears("*$", 1..=1, sum)
+            .iter()
+            .map(|(symbol, sum)| (symbol.y, *sum))
+            .collect();
+        assert_eq!(sums, vec![(4, 617), (8, 664)]);
+
+        let unattached: Vec<u32> = schematic.unattached().iter().map(|l| l.number).collect();
+        assert_eq!(unattached, vec![114, 58]);
+    }
+}
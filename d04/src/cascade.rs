@@ -0,0 +1,94 @@
+use std::str::FromStr;
+
+// Which cards a winning card hands out copies of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyRule {
+    // one copy each of the next `wins` cards, like the puzzle says
+    Following,
+    // one copy each of the next `wins * n` cards
+    Scaled(usize),
+}
+
+impl CopyRule {
+    fn copies(&self, wins: usize) -> usize {
+        match self {
+            CopyRule::Following => wins,
+            CopyRule::Scaled(n) => wins * n,
+        }
+    }
+}
+
+// "following" or "scaled:2"
+impl FromStr for CopyRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "following" => Ok(CopyRule::Following),
+            Some(("scaled", n)) => n.parse().map(CopyRule::Scaled).map_err(|_| format!("Bad scale in {s}")),
+            _ => Err(format!("Unknown copy rule {s}, expected following or scaled:N")),
+        }
+    }
+}
+
+// How many of each card we end up with, and where they came from.
+// Cards are tracked by their position in the table rather than their id, so ids can be anything.
+#[derive(Debug, PartialEq)]
+pub struct Cascade {
+    pub counts: Vec<u64>,
+    // for each card, (position of the card that won it copies, how many)
+    pub sources: Vec<Vec<(usize, u64)>>,
+    // (position of the card, how many of the cards it won are past the end of the table)
+    pub overflows: Vec<(usize, usize)>,
+}
+
+impl Cascade {
+    // Each card only ever copies cards after it, so one pass in table order settles every count.
+    pub fn run(wins: &[usize], rule: CopyRule) -> Cascade {
+        let mut counts = vec![1; wins.len()];
+        let mut sources = vec![vec![]; wins.len()];
+        let mut overflows = vec![];
+        for (position, win_count) in wins.iter().enumerate() {
+            let copies = rule.copies(*win_count);
+            let end = position + 1 + copies;
+            if end > wins.len() {
+                overflows.push((position, end - wins.len()));
+            }
+            for copied in position + 1..end.min(wins.len()) {
+                counts[copied] += counts[position];
+                sources[copied].push((position, counts[position]));
+            }
+        }
+
+        Cascade { counts, sources, overflows }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let cascade = Cascade::run(&[4, 2, 2, 1, 0, 0], CopyRule::Following);
+        assert_eq!(cascade.counts, vec![1, 2, 4, 8, 14, 1]);
+        assert_eq!(cascade.total(), 30);
+        assert_eq!(cascade.sources[2], vec![(0, 1), (1, 2)]);
+        assert!(cascade.overflows.is_empty());
+    }
+
+    #[test]
+    fn overflow_and_rules() {
+        let cascade = Cascade::run(&[1, 0, 2], CopyRule::Scaled(2));
+        assert_eq!(cascade.counts, vec![1, 2, 2]);
+        assert_eq!(cascade.overflows, vec![(2, 4)]);
+
+        assert_eq!("following".parse(), Ok(CopyRule::Following));
+        assert_eq!("scaled:3".parse(), Ok(CopyRule::Scaled(3)));
+        assert!("sideways".parse::<CopyRule>().is_err());
+    }
+}
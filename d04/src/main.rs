@@ -1,6 +1,12 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::fs;
-use std::ops::Range;
+use libaoc::config::Config;
+use libaoc::{debug, trace, warn};
+use crate::cascade::{Cascade, CopyRule};
+use crate::scoring::ScoringRule;
+
+mod cascade;
+mod scoring;
 
 #[derive(PartialEq, Eq, Debug)]
 struct Card {
@@ -15,25 +21,28 @@ impl Card {
         let parts: Vec<&str> = line.split(": ").collect();
         let id: u32 = parts[0].split(" ").last().unwrap().parse().unwrap();
         let number_sets: Vec<&str> = parts[1].split(" | ").collect();
-        let winners: HashSet<u32> = Self::extract_numbers(number_sets[0]);
-        let own_numbers = Self::extract_numbers(number_sets[1]);
+        let winners: HashSet<u32> = Self::extract_numbers(id, number_sets[0]);
+        let own_numbers = Self::extract_numbers(id, number_sets[1]);
 
         Card {id, winners, own_numbers}
     }
 
-    fn extract_numbers(numbers: &str) -> HashSet<u32> {
+    // any amount of numbers; a repeated one only counts once, so say so
+    fn extract_numbers(id: u32, numbers: &str) -> HashSet<u32> {
+        let mut set = HashSet::new();
         numbers
             .split_whitespace()
             .map(|d| d.parse::<u32>().unwrap())
-            .collect()
+            .for_each(|number| {
+                if !set.insert(number) {
+                    warn!("Card {id} has {number} more than once");
+                }
+            });
+        set
     }
 
-    fn score(&self) -> u32 {
-        let overlap = self.overlap();
-        match overlap {
-            0 => 0,
-            _ => 1 << (overlap - 1)
-        }
+    fn score(&self, rule: &dyn ScoringRule) -> u128 {
+        rule.score(self.overlap() as usize)
     }
 
     fn overlap(&self) -> u32 {
@@ -42,42 +51,40 @@ impl Card {
 }
 
 fn main() {
-    part1();
-    part2();
+    let config = Config::load();
+    part1(&config);
+    part2(&config);
 }
 
-fn part1() {
+// `--scoring=linear`, `fibonacci` or `table:0,1,3,7` for other ways of playing (doubling)
+fn part1(config: &Config) {
     let cards = load_cards();
+    let rule = scoring::from_name(&config.get("scoring", String::from("doubling")));
 
-    let score: u32 = cards.iter()
-        .map(|card| card.score())
+    let score: u128 = cards.iter()
+        .map(|card| card.score(rule.as_ref()))
         .sum();
 
     println!("Part 1: {score}");
 }
 
-fn part2() {
+// `--copy_rule=scaled:2` hands out more copies per win; -v shows how many of each card we got and from where
+fn part2(config: &Config) {
     let cards = load_cards();
-    let mut card_counts: HashMap<u32, u64> = HashMap::new();
-    cards.iter()
-        .for_each(|card| {
-            card_counts.insert(card.id, 1);
-        });
-    cards.iter()
-        .for_each(|card| {
-            let win_count = card.overlap();
-            let self_count = card_counts.get(&card.id).unwrap().clone();
-
-            let dup_range = Range {start: card.id + 1, end: card.id + win_count + 1};
-            for duplicated_card in dup_range {
-                let new_count = card_counts.get(&duplicated_card).unwrap().clone() + self_count;
-                card_counts.insert(duplicated_card, new_count);
-            }
-        });
-
-    let cards_total: u64 = card_counts.values().sum();
-
-    println!("Part 2: {cards_total}");
+    let wins: Vec<usize> = cards.iter().map(|card| card.overlap() as usize).collect();
+    let cascade = Cascade::run(&wins, config.get("copy_rule", CopyRule::Following));
+
+    for (position, count) in cascade.counts.iter().enumerate() {
+        debug!("Card {}: {count} copies", cards[position].id);
+        for (source, copies) in cascade.sources[position].iter() {
+            trace!("  {copies} from card {}", cards[*source].id);
+        }
+    }
+    for (position, past_end) in cascade.overflows.iter() {
+        warn!("Card {} wins copies of {past_end} cards past the end of the table", cards[*position].id);
+    }
+
+    println!("Part 2: {}", cascade.total());
 }
 
 fn load_cards() -> Vec<Card> {
@@ -91,6 +98,7 @@ fn load_cards() -> Vec<Card> {
 #[cfg(test)]
 mod tests {
     use crate::Card;
+    use crate::scoring::Doubling;
 
     #[test]
     fn card_stuff() {
@@ -101,10 +109,14 @@ mod tests {
         assert!(test_card1.own_numbers.contains(&3));
         assert!(test_card1.own_numbers.contains(&4));
         assert_eq!(test_card1.overlap(), 0);
-        assert_eq!(test_card1.score(), 0);
+        assert_eq!(test_card1.score(&Doubling), 0);
 
         let test_card2 = Card::from_line("Card 2: 10 11 12 13 | 11 12 13 14");
         assert_eq!(test_card2.overlap(), 3);
-        assert_eq!(test_card2.score(), 4);
+        assert_eq!(test_card2.score(&Doubling), 4);
+
+        let test_card3 = Card::from_line("Card 30: 1 2 2 3 4 5 6 | 2 2 6");
+        assert_eq!(test_card3.winners.len(), 6);
+        assert_eq!(test_card3.overlap(), 2);
     }
 }
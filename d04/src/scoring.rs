@@ -0,0 +1,87 @@
+// How many points a card with this many matching numbers is worth.
+pub trait ScoringRule {
+    fn score(&self, matches: usize) -> u128;
+}
+
+// 1, 2, 4, 8, ... as the puzzle says
+pub struct Doubling;
+
+impl ScoringRule for Doubling {
+    fn score(&self, matches: usize) -> u128 {
+        match matches {
+            0 => 0,
+            _ => 1u128.checked_shl(matches as u32 - 1)
+                .unwrap_or_else(|| panic!("A score for {matches} matches doesn't fit in 128 bits"))
+        }
+    }
+}
+
+// a point per match
+pub struct Linear;
+
+impl ScoringRule for Linear {
+    fn score(&self, matches: usize) -> u128 {
+        matches as u128
+    }
+}
+
+// 1, 1, 2, 3, 5, ...
+pub struct Fibonacci;
+
+impl ScoringRule for Fibonacci {
+    fn score(&self, matches: usize) -> u128 {
+        let (mut a, mut b) = (0u128, 1u128);
+        for _ in 0..matches {
+            (a, b) = (b, a.checked_add(b)
+                .unwrap_or_else(|| panic!("A score for {matches} matches doesn't fit in 128 bits")));
+        }
+        a
+    }
+}
+
+// scores[matches], with anything past the end of the table worth the last entry
+pub struct Table(pub Vec<u128>);
+
+impl ScoringRule for Table {
+    fn score(&self, matches: usize) -> u128 {
+        self.0.get(matches).or(self.0.last()).copied().unwrap_or(0)
+    }
+}
+
+// doubling, linear, fibonacci, or table:0,1,3,7
+pub fn from_name(name: &str) -> Box<dyn ScoringRule> {
+    match name.split_once(':') {
+        None if name == "doubling" => Box::new(Doubling),
+        None if name == "linear" => Box::new(Linear),
+        None if name == "fibonacci" => Box::new(Fibonacci),
+        Some(("table", scores)) => Box::new(Table(scores.split(',')
+            .map(|score| score.trim().parse()
+                .unwrap_or_else(|_| panic!("Couldn't parse score {score} in {name}")))
+            .collect())),
+        _ => panic!("Unknown scoring rule {name}, expected doubling, linear, fibonacci or table:..."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules() {
+        let scores = |rule: &dyn ScoringRule| (0..6).map(|n| rule.score(n)).collect::<Vec<u128>>();
+        assert_eq!(scores(&Doubling), vec![0, 1, 2, 4, 8, 16]);
+        assert_eq!(scores(&Linear), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(scores(&Fibonacci), vec![0, 1, 1, 2, 3, 5]);
+        assert_eq!(scores(from_name("table:0,1,3,7").as_ref()), vec![0, 1, 3, 7, 7, 7]);
+
+        // past what the old `1 << (overlap - 1)` could hold
+        assert_eq!(Doubling.score(40), 1 << 39);
+        assert_eq!(Doubling.score(128), 1 << 127);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit in 128 bits")]
+    fn too_many_matches() {
+        Doubling.score(129);
+    }
+}
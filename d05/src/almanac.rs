@@ -0,0 +1,80 @@
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+use crate::{compose, DirtMap};
+
+impl DirtMap {
+    // "seed-to-soil map:" is ("seed", "soil")
+    pub fn categories(&self) -> Option<(&str, &str)> {
+        self.name.trim_end_matches(':').trim_end_matches(" map").split_once("-to-")
+    }
+}
+
+// The maps keyed by the category they convert from, so conversions can go between any two categories
+// that are connected, whatever order the maps came in.
+pub struct Almanac {
+    maps: Vec<DirtMap>,
+    // source category => indexes of the maps out of it
+    edges: HashMap<String, Vec<usize>>,
+}
+
+impl Almanac {
+    pub fn new(maps: Vec<DirtMap>) -> Almanac {
+        let mut edges: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, map) in maps.iter().enumerate() {
+            let (from, _) = map.categories()
+                .unwrap_or_else(|| panic!("Map header should look like `a-to-b map:`, not {}", map.name));
+            edges.entry(String::from(from)).or_default().push(idx);
+        }
+        Almanac {maps, edges}
+    }
+
+    // the maps to go through, in order, to get from one category to another (fewest first)
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<&DirtMap>, String> {
+        let mut came_by: HashMap<&str, Option<usize>> = HashMap::from([(from, None)]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                let mut path = Vec::new();
+                let mut current = to;
+                while let Some(Some(idx)) = came_by.get(current) {
+                    path.push(&self.maps[*idx]);
+                    current = self.maps[*idx].categories().unwrap().0;
+                }
+                path.reverse();
+                return Ok(path);
+            }
+            for idx in self.edges.get(category).into_iter().flatten() {
+                let (_, next) = self.maps[*idx].categories().unwrap();
+                if !came_by.contains_key(next) {
+                    came_by.insert(next, Some(*idx));
+                    queue.push_back(next);
+                }
+            }
+        }
+        Err(format!("No maps lead from {from} to {to}"))
+    }
+
+    pub fn convert(&self, value: i64, from: &str, to: &str) -> Result<i64, String> {
+        Ok(self.path(from, to)?.iter().fold(value, |val, map| map.transform(&val)))
+    }
+
+    // one map straight from one category to the other
+    pub fn composed(&self, from: &str, to: &str) -> Result<DirtMap, String> {
+        let path = self.maps_between(from, to)?;
+        if path.is_empty() {
+            return Ok(DirtMap {name: format!("{from}-to-{to} map:"), transforms: vec![]});
+        }
+        Ok(compose(&path))
+    }
+
+    pub fn convert_ranges(&self, ranges: &[Range<i64>], from: &str, to: &str)
+        -> Result<Vec<Range<i64>>, String> {
+        let map = self.composed(from, to)?;
+        Ok(ranges.iter().flat_map(|range| map.to_output_ranges(range)).collect())
+    }
+
+    // the maps in the order they go from one category to the other
+    pub fn maps_between(&self, from: &str, to: &str) -> Result<Vec<DirtMap>, String> {
+        Ok(self.path(from, to)?.into_iter().cloned().collect())
+    }
+}
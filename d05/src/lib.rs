@@ -1,9 +1,14 @@
-use std::cmp::{min, Ordering};
+use std::cmp::{max, min, Ordering};
+use std::fmt;
 use std::fs;
 use std::ops::Range;
 use itertools::Itertools;
+use libaoc::warn;
 
-#[derive(PartialEq, Eq, Hash, Debug)]
+pub mod almanac;
+pub mod validate;
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub struct DirtTransform {
     pub range: Range<i64>,
     pub transform: i64
@@ -17,6 +22,11 @@ impl DirtTransform {
     pub fn transform(&self, val: &i64) -> i64 {
         val + self.transform
     }
+
+    // where the range ends up
+    pub fn destination(&self) -> Range<i64> {
+        Range {start: self.transform(&self.range.start), end: self.transform(&self.range.end)}
+    }
 }
 
 impl PartialOrd for DirtTransform {
@@ -32,18 +42,160 @@ impl Ord for DirtTransform {
 }
 
 
-#[derive(PartialEq, Eq, Hash, Debug)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub struct DirtMap {
     pub name: String,
     pub transforms: Vec<DirtTransform>
 }
 
 impl DirtMap {
+    // the transforms are sorted and disjoint (parse_map sees to that), so binary search for the last one
+    // starting at or before source
     pub fn transform(&self, source: &i64) -> i64 {
-        self.transforms.iter()
-            .find(|xform| xform.contains(&source))
-            .map(|xform| xform.transform(&source))
-            .unwrap_or(source.clone())
+        let idx = self.transforms.partition_point(|xform| xform.range.start <= *source);
+        idx.checked_sub(1)
+            .map(|idx| &self.transforms[idx])
+            .filter(|xform| xform.contains(source))
+            .map(|xform| xform.transform(source))
+            .unwrap_or(*source)
+    }
+
+    // the smallest range outside of which every value maps to itself
+    fn domain(&self) -> Range<i64> {
+        match (self.transforms.first(), self.transforms.iter().map(|xform| xform.range.end).max()) {
+            (Some(first), Some(end)) => Range {start: first.range.start, end},
+            _ => Range {start: 0, end: 0}
+        }
+    }
+
+    // the transforms over `domain`, with the gaps between them filled in by do-nothing transforms
+    fn explicit(&self, domain: &Range<i64>) -> Vec<DirtTransform> {
+        let mut pieces = Vec::new();
+        let mut cursor = domain.start;
+        for xform in self.transforms.iter() {
+            if cursor < xform.range.start {
+                pieces.push(DirtTransform {range: Range {start: cursor, end: xform.range.start}, transform: 0});
+            }
+            pieces.push(DirtTransform {range: xform.range.clone(), transform: xform.transform});
+            cursor = max(cursor, xform.range.end);
+        }
+        if cursor < domain.end {
+            pieces.push(DirtTransform {range: Range {start: cursor, end: domain.end}, transform: 0});
+        }
+        pieces
+    }
+
+    // One map that does what this map and then `next` would do.
+    // Inside the combined domain of the two maps every stretch is spelled out, do-nothing ones included;
+    // outside of it both maps leave values alone, so the combination does too.
+    pub fn then(&self, next: &DirtMap) -> DirtMap {
+        let (mine, theirs) = (self.domain(), next.domain());
+        let domain = match (mine.is_empty(), theirs.is_empty()) {
+            (true, true) => mine,
+            (true, false) => theirs,
+            (false, true) => mine,
+            (false, false) => Range {start: min(mine.start, theirs.start), end: max(mine.end, theirs.end)},
+        };
+        let next_pieces = next.explicit(&domain);
+
+        let mut transforms: Vec<DirtTransform> = Vec::new();
+        for piece in self.explicit(&domain) {
+            // where this piece's values land, split up by whichever of next's pieces they land in
+            let image = Range {start: piece.transform(&piece.range.start), end: piece.transform(&piece.range.end)};
+            let mut covered = Vec::new();
+            for next_piece in next_pieces.iter() {
+                let overlap = Range {
+                    start: max(image.start, next_piece.range.start),
+                    end: min(image.end, next_piece.range.end)
+                };
+                if !overlap.is_empty() {
+                    covered.push((overlap.clone(), next_piece.transform));
+                }
+            }
+            // anything that lands outside of next's domain is left alone by next
+            covered.sort_by_key(|(range, _)| range.start);
+            let mut cursor = image.start;
+            let mut gaps = Vec::new();
+            for (range, _) in covered.iter() {
+                if cursor < range.start {
+                    gaps.push((Range {start: cursor, end: range.start}, 0));
+                }
+                cursor = max(cursor, range.end);
+            }
+            if cursor < image.end {
+                gaps.push((Range {start: cursor, end: image.end}, 0));
+            }
+            covered.extend(gaps);
+
+            for (range, next_transform) in covered {
+                transforms.push(DirtTransform {
+                    range: Range {start: range.start - piece.transform, end: range.end - piece.transform},
+                    transform: piece.transform + next_transform
+                });
+            }
+        }
+        transforms.sort();
+
+        // neighbors that do the same thing might as well be one transform
+        let mut merged: Vec<DirtTransform> = Vec::new();
+        for xform in transforms {
+            match merged.last_mut() {
+                Some(last) if last.range.end == xform.range.start && last.transform == xform.transform =>
+                    last.range.end = xform.range.end,
+                _ => merged.push(xform)
+            }
+        }
+
+        DirtMap {name: compose_names(&self.name, &next.name), transforms: merged}
+    }
+
+    // Every source value that ends up somewhere in `range`: whatever the transforms move into it,
+    // plus the parts of it no transform touches, since those map to themselves.
+    // Destination ranges are allowed to overlap, in which case a value has more than one source.
+    pub fn preimage(&self, range: &Range<i64>) -> Vec<Range<i64>> {
+        let mut sources: Vec<Range<i64>> = self.transforms.iter()
+            .map(|xform| {
+                let destination = xform.destination();
+                let overlap = intersect(&destination, range);
+                Range {start: overlap.start - xform.transform, end: overlap.end - xform.transform}
+            })
+            .filter(|source| !source.is_empty())
+            .collect();
+
+        let mut cursor = range.start;
+        for xform in self.transforms.iter() {
+            let untouched = Range {start: cursor, end: min(xform.range.start, range.end)};
+            if !untouched.is_empty() {
+                sources.push(untouched);
+            }
+            cursor = max(cursor, xform.range.end);
+        }
+        if cursor < range.end {
+            sources.push(Range {start: cursor, end: range.end});
+        }
+
+        merge_ranges(sources)
+    }
+
+    // The map that undoes this one. That only exists if no two values end up in the same place,
+    // so we get None if destination ranges overlap each other or land on values that map to themselves.
+    pub fn inverse(&self) -> Option<DirtMap> {
+        let destinations: Vec<Range<i64>> = self.transforms.iter()
+            .map(|xform| xform.destination())
+            .collect();
+        let total = |ranges: &[Range<i64>]| ranges.iter().map(|r| r.end - r.start).sum::<i64>();
+        let merged_destinations = merge_ranges(destinations.clone());
+        let merged_sources = merge_ranges(self.transforms.iter().map(|xform| xform.range.clone()).collect());
+        if total(&merged_destinations) != total(&destinations) || merged_destinations != merged_sources {
+            return None;
+        }
+
+        let transforms = destinations.into_iter()
+            .zip(self.transforms.iter())
+            .map(|(range, xform)| DirtTransform {range, transform: -xform.transform})
+            .sorted()
+            .collect();
+        Some(DirtMap {name: invert_name(&self.name), transforms})
     }
 
     // take a single input range,
@@ -52,7 +204,9 @@ impl DirtMap {
     pub fn to_output_ranges(&self, input_range: &Range<i64>) -> Vec<Range<i64>> {
         let mut output_ranges = Vec::new();
         let mut cur_range = input_range.clone();
-        let mut xform_iter = self.transforms.iter();
+        // skip straight past the transforms that end before the range starts
+        let first = self.transforms.partition_point(|xform| xform.range.end <= input_range.start);
+        let mut xform_iter = self.transforms[first..].iter();
         loop {
             // if the range has been consumed, break
             if cur_range.is_empty() {
@@ -127,6 +281,99 @@ impl DirtMap {
     }
 }
 
+// the same format as the almanac: a header, then `destination source length` lines
+impl fmt::Display for DirtMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        for xform in self.transforms.iter() {
+            let length = xform.range.end - xform.range.start;
+            write!(f, "\n{} {} {length}", xform.transform(&xform.range.start), xform.range.start)?;
+        }
+        Ok(())
+    }
+}
+
+// "seed-to-soil map:" then "soil-to-fertilizer map:" makes "seed-to-fertilizer map:"
+fn compose_names(first: &str, second: &str) -> String {
+    let categories = |name: &str| name.trim_end_matches(" map:").split_once("-to-")
+        .map(|(from, to)| (String::from(from), String::from(to)));
+    match (categories(first), categories(second)) {
+        (Some((from, _)), Some((_, to))) => format!("{from}-to-{to} map:"),
+        _ => format!("{} then {}", first.trim_end_matches(':'), second)
+    }
+}
+
+// "seed-to-soil map:" backwards is "soil-to-seed map:"
+fn invert_name(name: &str) -> String {
+    match name.trim_end_matches(" map:").split_once("-to-") {
+        Some((from, to)) => format!("{to}-to-{from} map:"),
+        None => format!("inverse {name}")
+    }
+}
+
+fn intersect(a: &Range<i64>, b: &Range<i64>) -> Range<i64> {
+    Range {start: max(a.start, b.start), end: min(a.end, b.end)}
+}
+
+// sort the ranges and join up any that overlap or touch
+pub fn merge_ranges(mut ranges: Vec<Range<i64>>) -> Vec<Range<i64>> {
+    ranges.retain(|range| !range.is_empty());
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<i64>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = max(last.end, range.end),
+            _ => merged.push(range)
+        }
+    }
+    merged
+}
+
+// every seed that ends up at a location in `range`, walking the layers backwards
+pub fn preimage(maps: &[DirtMap], range: &Range<i64>) -> Vec<Range<i64>> {
+    maps.iter().rev()
+        .fold(vec![range.clone()], |ranges, map| {
+            merge_ranges(ranges.iter().flat_map(|range| map.preimage(range)).collect())
+        })
+}
+
+// Part 2 the other way around: look at locations from the lowest one anything could reach upwards,
+// in ever bigger steps, until some of them come from a seed we have. Those seeds can't land anywhere lower.
+pub fn lowest_location_from(maps: &[DirtMap], seed_ranges: &[Range<i64>]) -> Option<i64> {
+    // every location is a seed no map touched or somewhere a transform sends things,
+    // so nothing can land outside of these
+    let destinations = maps.iter().flat_map(|map| map.transforms.iter()).map(|xform| xform.destination());
+    let reachable = seed_ranges.iter().cloned().chain(destinations);
+    let lowest = reachable.clone().map(|r| r.start).min()?;
+    let highest = reachable.map(|r| r.end).max()?;
+
+    let (mut low, mut step) = (lowest, 1);
+    while low < highest {
+        let locations = Range {start: low, end: low + step};
+        let seeds: Vec<Range<i64>> = preimage(maps, &locations).iter()
+            .flat_map(|source| seed_ranges.iter().map(|seeds| intersect(source, seeds)))
+            .filter(|seeds| !seeds.is_empty())
+            .collect();
+        if !seeds.is_empty() {
+            let almanac = compose(maps);
+            return seeds.iter()
+                .flat_map(|range| almanac.to_output_ranges(range))
+                .map(|range| range.start)
+                .min();
+        }
+        low += step;
+        step *= 2;
+    }
+    None
+}
+
+// the whole almanac as one map, straight from seeds to locations
+pub fn compose(maps: &[DirtMap]) -> DirtMap {
+    let mut maps = maps.iter();
+    let first = maps.next().expect("Need at least one map to compose");
+    let start = DirtMap {name: first.name.clone(), transforms: first.explicit(&first.domain())};
+    maps.fold(start, |composed, map| composed.then(map))
+}
 
 pub fn load(filename: &str) -> (Vec<i64>, Vec<DirtMap>) {
     let file_str = fs::read_to_string(filename)
@@ -150,11 +397,36 @@ pub fn load(filename: &str) -> (Vec<i64>, Vec<DirtMap>) {
 pub fn parse_map(block: &str) -> DirtMap {
     let mut lines = block.lines();
     let name = String::from(lines.next().unwrap());
-    let transforms: Vec<DirtTransform> = lines
+    // an empty range can't transform anything (`--check` points them out)
+    let sorted: Vec<DirtTransform> = lines
         .map(parse_transform)
+        .filter(|xform| {
+            if xform.range.is_empty() {
+                warn!("{name} skipping the empty range at {}", xform.range.start);
+            }
+            !xform.range.is_empty()
+        })
         .sorted()
         .collect();
 
+    // Where source ranges overlap, the first one (by start, then by line) wins, just as it did when
+    // lookups took the first transform containing the value. Trimming each transform down to what
+    // the ones before it don't cover keeps that, and everything after this can count on the
+    // transforms being sorted and disjoint.
+    let mut transforms: Vec<DirtTransform> = Vec::new();
+    let mut covered_to = i64::MIN;
+    for xform in sorted {
+        let start = max(xform.range.start, covered_to);
+        if start > xform.range.start {
+            warn!("{name} source range {:?} overlaps an earlier one, so only {:?} of it counts",
+                  xform.range, start..max(start, xform.range.end));
+        }
+        if start < xform.range.end {
+            transforms.push(DirtTransform {range: Range {start, end: xform.range.end}, transform: xform.transform});
+        }
+        covered_to = max(covered_to, xform.range.end);
+    }
+
     DirtMap {name, transforms}
 }
 
@@ -163,7 +435,9 @@ pub fn parse_transform(line: &str) -> DirtTransform {
         .map(|num| num.parse::<i64>().unwrap())
         .collect();
     assert_eq!(parts.len(), 3);
-    let range = Range {start: parts[1], end: parts[1] + parts[2]};
+    let end = parts[1].checked_add(parts[2])
+        .unwrap_or_else(|| panic!("{line} goes past the biggest i64 (run with --check)"));
+    let range = Range {start: parts[1], end};
     let transform = parts[0] - parts[1];
 
     DirtTransform { range, transform }
@@ -1,22 +1,52 @@
 use std::ops::Range;
 use std::cmp::Ord;
 use itertools::{Itertools};
-use d05::load;
+use std::fs;
+use std::process::exit;
+use libaoc::config::Config;
+use libaoc::debug;
+use d05::{compose, load, DirtMap};
+use d05::almanac::Almanac;
+use d05::validate::{validate, Severity};
 
 fn main() {
+    let config = Config::load();
+    if config.flag("check") {
+        check("input");
+    }
     part1();
     part2();
 }
 
-fn part1() {
+// `--check` looks the almanac over before using it, and stops if the maps don't make sense
+fn check(filename: &str) {
+    let contents = fs::read_to_string(filename)
+        .unwrap();
+    let issues = validate(&contents);
+    for issue in issues.iter() {
+        eprintln!("{filename}: {issue}");
+    }
+    if issues.iter().any(|issue| issue.severity == Severity::Error) {
+        exit(1);
+    }
+}
+
+// find the maps from seed to location by their names, whatever order they're in
+fn seeds_to_locations() -> (Vec<i64>, Vec<DirtMap>) {
     let (seeds, maps) = load("input");
+    let maps = Almanac::new(maps).maps_between("seed", "location")
+        .unwrap_or_else(|e| panic!("{e}"));
+    (seeds, maps)
+}
+
+fn part1() {
+    let (seeds, maps) = seeds_to_locations();
+    let almanac = compose(&maps);
+    debug!("{almanac}");
 
-    // for each seed, do the layered transforms in-order
+    // one lookup per seed takes it through every layer at once
     let transformed_seeds: Vec<i64> = seeds.iter()
-        .map(|seed| maps.iter()
-            .fold(seed.clone(),
-                  |input, dirtmap| dirtmap.transform(&input)
-            ))
+        .map(|seed| almanac.transform(seed))
         .collect();
 
     let min_seed = transformed_seeds.iter().min().unwrap();
@@ -25,7 +55,7 @@ fn part1() {
 }
 
 fn part2() {
-    let (seeds, maps) = load("input");
+    let (seeds, maps) = seeds_to_locations();
 
     // parse the seeds in to chunks of 2
     let seed_ranges: Vec<Range<i64>> = seeds.iter()
@@ -36,21 +66,11 @@ fn part2() {
         .map(|c| Range {start: *c[0], end: c[0] + c[1]})
         .collect();
 
-    // we're going to process all of the ranges at our current layer
-    // before moving onto the next layer
-    let mut layer_ranges = seed_ranges.clone();
-
-    for layer in maps {
-        // for each range in our current layer...
-        let output_ranges: Vec<Range<i64>> = layer_ranges.iter()
-            // ... transform it into next-layer ranges ...
-            .map(|range| layer.to_output_ranges(range))
-            // ... then collapse the iter<Vec<Range<> into an iter<Range<>...
-            .flatten()
-            // ... and collect the iter<Range<> into a Vec<Range<>.
-            .collect();
-        layer_ranges = output_ranges;
-    }
+    // with every layer composed into one map, each seed range only needs splitting up once
+    let almanac = compose(&maps);
+    let layer_ranges: Vec<Range<i64>> = seed_ranges.iter()
+        .flat_map(|range| almanac.to_output_ranges(range))
+        .collect();
 
     let smallest_range_begin = layer_ranges.iter()
         .map(|r| r.start)
@@ -0,0 +1,142 @@
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    // the answer might be surprising, but the maps still make sense
+    Warning,
+    // the maps don't make sense, so the answers can't be trusted
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    // counting from 1, like an editor does
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "line {}: {severity}: {}", self.line, self.message)
+    }
+}
+
+// one `destination source length` line
+struct Line {
+    number: usize,
+    source: Range<i64>,
+    destination: Range<i64>,
+}
+
+// Look over an almanac for things that would trip up the maps:
+// source ranges that overlap (only the first one counts where they do), empty ranges,
+// numbers too big to add up, and two ranges sending values to the same place.
+pub fn validate(contents: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut map_lines: Vec<Line> = Vec::new();
+    for (idx, text) in contents.lines().enumerate() {
+        let number = idx + 1;
+        let text = text.trim();
+        if text.ends_with("map:") {
+            check_map(&map_lines, &mut issues);
+            map_lines.clear();
+        } else if let Some(seeds) = text.strip_prefix("seeds:") {
+            if seeds.split_whitespace().count() % 2 == 1 {
+                let message = String::from("odd number of seeds, so the last one has no range length for part 2");
+                issues.push(Issue {severity: Severity::Warning, line: number, message});
+            }
+        } else if !text.is_empty() {
+            if let Some(line) = check_line(number, text, &mut issues) {
+                map_lines.push(line);
+            }
+        }
+    }
+    check_map(&map_lines, &mut issues);
+
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+fn check_line(number: usize, text: &str, issues: &mut Vec<Issue>) -> Option<Line> {
+    let mut error = |message: String| issues.push(Issue {severity: Severity::Error, line: number, message});
+    let parts: Vec<i64> = match text.split_whitespace().map(|n| n.parse::<i64>()).collect() {
+        Ok(parts) => parts,
+        Err(_) => {
+            error(format!("expected three numbers, got `{text}`"));
+            return None;
+        }
+    };
+    if parts.len() != 3 {
+        error(format!("expected three numbers, got {}", parts.len()));
+        return None;
+    }
+    let (dest, source, length) = (parts[0], parts[1], parts[2]);
+    if length < 0 {
+        error(format!("negative length {length}"));
+        return None;
+    }
+    let (Some(source_end), Some(dest_end)) = (source.checked_add(length), dest.checked_add(length)) else {
+        error(format!("{source} + {length} or {dest} + {length} is too big for an i64"));
+        return None;
+    };
+    if length == 0 {
+        issues.push(Issue {severity: Severity::Warning, line: number,
+            message: String::from("zero-length range doesn't map anything")});
+        return None;
+    }
+
+    Some(Line {number, source: source..source_end, destination: dest..dest_end})
+}
+
+fn check_map(lines: &[Line], issues: &mut Vec<Issue>) {
+    for (idx, first) in lines.iter().enumerate() {
+        for second in lines[idx + 1..].iter() {
+            if overlap(&first.source, &second.source) {
+                issues.push(Issue {severity: Severity::Error, line: second.number,
+                    message: format!("source range {:?} overlaps {:?} from line {}",
+                                     second.source, first.source, first.number)});
+            }
+            if overlap(&first.destination, &second.destination) {
+                issues.push(Issue {severity: Severity::Warning, line: second.number,
+                    message: format!("destination range {:?} overlaps {:?} from line {}",
+                                     second.destination, first.destination, first.number)});
+            }
+        }
+    }
+}
+
+fn overlap(a: &Range<i64>, b: &Range<i64>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_problems() {
+        let almanac = "seeds: 1 2 3\n\n\
+                       seed-to-soil map:\n50 98 2\n52 50 48\n60 90 10\n1 1 0\n\n\
+                       soil-to-fertilizer map:\n0 9223372036854775807 2\n1 2\n0 15 37\n10 52 2\n";
+        let issues: Vec<(usize, Severity)> = validate(almanac).iter()
+            .map(|issue| (issue.line, issue.severity))
+            .collect();
+        assert_eq!(issues, vec![
+            (1, Severity::Warning),
+            (6, Severity::Error),
+            (6, Severity::Error),
+            (6, Severity::Warning),
+            (7, Severity::Warning),
+            (10, Severity::Error),
+            (11, Severity::Error),
+            (13, Severity::Warning),
+        ]);
+        assert!(validate("seeds: 1 2\n\na-to-b map:\n50 98 2\n").is_empty());
+    }
+}
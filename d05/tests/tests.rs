@@ -1,5 +1,6 @@
 use std::ops::Range;
-use d05::{DirtMap, DirtTransform, parse_transform};
+use libaoc::difftest::{Differential, Rng, shrink_int, shrink_vec};
+use d05::{compose, lowest_location_from, preimage, DirtMap, DirtTransform, parse_map, parse_transform};
 
 #[test]
 fn transform_test() {
@@ -154,3 +155,252 @@ fn complete_overlap() {
         Range {start: 250, end: 300},
     ]);
 }
+
+
+// (destination start, source start, length), just like a line in the almanac
+type AlmanacLine = (i64, i64, i64);
+// a few layers of maps, and a range of seeds (start, length) to push through them
+type Almanac = (Vec<Vec<AlmanacLine>>, (i64, i64));
+
+fn random_layer(rng: &mut Rng) -> Vec<AlmanacLine> {
+    // lay the source ranges out left-to-right so they never overlap
+    let mut cursor = 0;
+    (0..rng.range(0..5))
+        .map(|_| {
+            let source = cursor + rng.range(0..5);
+            let length = rng.range(1..10);
+            cursor = source + length;
+            (rng.range(-30..60), source, length)
+        })
+        .collect()
+}
+
+// a seed taken through every layer one lookup at a time
+fn through(maps: &[DirtMap], seed: i64) -> i64 {
+    maps.iter().fold(seed, |val, map| map.transform(&val))
+}
+
+// a few random layers, and a range to push through them, shrinking towards smaller almanacs
+fn almanacs() -> Differential<Almanac> {
+    Differential::new(|rng| {
+        let layers = (0..rng.range(1..4))
+            .map(|_| random_layer(rng))
+            .collect();
+        (layers, (rng.range(0..80), rng.range(1..20)))
+    })
+        .shrink_with(shrink_almanac)
+}
+
+fn to_dirt_maps(layers: &[Vec<AlmanacLine>]) -> Vec<DirtMap> {
+    layers.iter().map(|layer| to_dirt_map(layer)).collect()
+}
+
+fn to_dirt_map(layer: &[AlmanacLine]) -> DirtMap {
+    let block = layer.iter()
+        .map(|(dest, source, length)| format!("{dest} {source} {length}"))
+        .fold(String::from("a-to-b map:"), |block, line| block + "\n" + &line);
+    parse_map(&block)
+}
+
+// shrinking a source start could make two transforms overlap, so only the others shrink
+fn shrink_almanac((layers, (seed_start, seed_length)): &Almanac) -> Vec<Almanac> {
+    let mut candidates: Vec<Almanac> = shrink_vec(layers, |layer| {
+        shrink_vec(layer, |(dest, source, length)| {
+            let mut lines: Vec<AlmanacLine> = shrink_int(*dest).into_iter()
+                .map(|d| (d, *source, *length))
+                .collect();
+            lines.extend(shrink_int(*length).into_iter()
+                .filter(|l| *l > 0)
+                .map(|l| (*dest, *source, l)));
+            lines
+        })
+    })
+        .into_iter()
+        .map(|smaller| (smaller, (*seed_start, *seed_length)))
+        .collect();
+    candidates.extend(shrink_int(*seed_start).into_iter()
+        .map(|s| (layers.clone(), (s, *seed_length))));
+    candidates.extend(shrink_int(*seed_length).into_iter()
+        .filter(|l| *l > 0)
+        .map(|l| (layers.clone(), (*seed_start, l))));
+    candidates
+}
+
+#[test]
+fn ranges_match_pointwise_transforms() {
+    almanacs().assert_agree(
+        |(layers, (seed_start, seed_length))| {
+            let maps = to_dirt_maps(layers);
+            let mut locations: Vec<i64> = (*seed_start..(seed_start + seed_length))
+                .map(|seed| through(&maps, seed))
+                .collect();
+            locations.sort();
+            locations
+        },
+        |(layers, (seed_start, seed_length))| {
+            let mut ranges = vec![Range {start: *seed_start, end: seed_start + seed_length}];
+            for layer in layers {
+                let map = to_dirt_map(layer);
+                ranges = ranges.iter()
+                    .flat_map(|range| map.to_output_ranges(range))
+                    .collect();
+            }
+            let mut locations: Vec<i64> = ranges.into_iter().flatten().collect();
+            locations.sort();
+            locations
+        });
+}
+
+#[test]
+fn composing_maps() {
+    let seed_to_soil = parse_map("seed-to-soil map:\n50 98 2\n52 50 48");
+    let soil_to_fertilizer = parse_map("soil-to-fertilizer map:\n0 15 37\n37 52 2\n39 0 15");
+    let composed = seed_to_soil.then(&soil_to_fertilizer);
+
+    for seed in -5..110 {
+        assert_eq!(composed.transform(&seed), soil_to_fertilizer.transform(&seed_to_soil.transform(&seed)));
+    }
+    assert_eq!(composed.to_string(), "seed-to-fertilizer map:\n\
+                                      39 0 15\n0 15 35\n37 50 2\n54 52 46\n35 98 2");
+}
+
+#[test]
+fn composed_almanac_matches_layers() {
+    almanacs().assert_agree(
+        |(layers, (seed_start, seed_length))| {
+            let maps = to_dirt_maps(layers);
+            (*seed_start..(seed_start + seed_length))
+                .map(|seed| through(&maps, seed))
+                .collect::<Vec<i64>>()
+        },
+        |(layers, (seed_start, seed_length))| {
+            let maps = to_dirt_maps(layers);
+            let composed = compose(&maps);
+            (*seed_start..(seed_start + seed_length))
+                .map(|seed| composed.transform(&seed))
+                .collect::<Vec<i64>>()
+        });
+}
+
+#[test]
+fn inverting_maps() {
+    let seed_to_soil = parse_map("seed-to-soil map:\n50 98 2\n52 50 48");
+    let soil_to_seed = seed_to_soil.inverse().unwrap();
+    assert_eq!(soil_to_seed.name, "soil-to-seed map:");
+    for seed in -5..110 {
+        assert_eq!(soil_to_seed.transform(&seed_to_soil.transform(&seed)), seed);
+    }
+
+    // 5 and 10 both end up at 10, so there's no going back
+    let squashed = parse_map("a-to-b map:\n10 5 1");
+    assert_eq!(squashed.inverse(), None);
+    assert_eq!(squashed.preimage(&Range {start: 8, end: 12}), vec![
+        Range {start: 5, end: 6},
+        Range {start: 8, end: 12},
+    ]);
+}
+
+#[test]
+fn overlapping_sources() {
+    // [0, 10) comes first, so it wins over [5, 8) wherever they overlap
+    let nested = parse_map("a-to-b map:\n100 0 10\n200 5 3");
+    assert_eq!(nested.transform(&9), 109);
+    assert_eq!(nested.transform(&6), 106);
+    assert_eq!(nested.transforms.len(), 1);
+
+    // an empty range is dropped rather than kept around
+    assert_eq!(parse_map("a-to-b map:\n100 0 0\n50 5 1").transforms.len(), 1);
+
+    // and [5, 15) only keeps the part past 10
+    let staggered = parse_map("a-to-b map:\n200 5 10\n100 0 10");
+    assert_eq!(staggered.transform(&9), 109);
+    assert_eq!(staggered.transform(&12), 207);
+    assert_eq!(staggered.to_output_ranges(&Range {start: 8, end: 12}), vec![
+        Range {start: 108, end: 110},
+        Range {start: 205, end: 207},
+    ]);
+}
+
+#[test]
+fn overlapping_lines_look_up_like_the_first_match() {
+    // any lines at all, empty and overlapping ones included
+    Differential::new(|rng| {
+        (0..rng.range(0..6))
+            .map(|_| (rng.range(0..60), rng.range(0..20), rng.range(0..10)))
+            .collect::<Vec<AlmanacLine>>()
+    })
+        .assert_agree(
+            |lines| {
+                // the old lookup: the first line containing the value, once sorted by source start
+                let mut sorted = lines.clone();
+                sorted.sort_by_key(|(_, source, _)| *source);
+                (-5..40)
+                    .map(|seed| sorted.iter()
+                        .find(|(_, source, length)| (*source..source + length).contains(&seed))
+                        .map(|(dest, source, _)| seed + dest - source)
+                        .unwrap_or(seed))
+                    .collect::<Vec<i64>>()
+            },
+            |lines| {
+                let map = to_dirt_map(lines);
+                (-5..40).map(|seed| map.transform(&seed)).collect::<Vec<i64>>()
+            });
+}
+
+#[test]
+fn preimages_match_brute_force() {
+    let window = -10..200;
+    almanacs().assert_agree(
+        |(layers, (start, length))| {
+            let maps = to_dirt_maps(layers);
+            let locations = *start..start + length;
+            window.clone()
+                .filter(|seed| locations.contains(&through(&maps, *seed)))
+                .collect::<Vec<i64>>()
+        },
+        |(layers, (start, length))| {
+            let maps = to_dirt_maps(layers);
+            preimage(&maps, &Range {start: *start, end: start + length}).into_iter()
+                .flatten()
+                .filter(|seed| window.contains(seed))
+                .collect::<Vec<i64>>()
+        });
+}
+
+#[test]
+fn lowest_location_backwards() {
+    almanacs().assert_agree(
+        |(layers, (start, length))| {
+            let maps = to_dirt_maps(layers);
+            (*start..start + length).map(|seed| through(&maps, seed)).min()
+        },
+        |(layers, (start, length))| {
+            lowest_location_from(&to_dirt_maps(layers), &[Range {start: *start, end: start + length}])
+        });
+
+    // seeds can end up below zero
+    let maps = vec![parse_map("a-to-b map:\n-50 10 5")];
+    assert_eq!(lowest_location_from(&maps, &[Range {start: 12, end: 20}]), Some(-48));
+}
+
+#[test]
+fn category_graph() {
+    // out of order, with a branch off to the side
+    let almanac = d05::almanac::Almanac::new(vec![
+        parse_map("soil-to-fertilizer map:\n0 15 37\n37 52 2\n39 0 15"),
+        parse_map("soil-to-rocks map:\n0 0 100"),
+        parse_map("seed-to-soil map:\n50 98 2\n52 50 48"),
+        parse_map("fertilizer-to-water map:\n49 53 8\n0 11 42\n42 0 7\n57 7 4"),
+    ]);
+    let names: Vec<&str> = almanac.path("seed", "water").unwrap().iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, vec!["seed-to-soil map:", "soil-to-fertilizer map:", "fertilizer-to-water map:"]);
+
+    // the example's seed 79 is soil 81, fertilizer 81 and water 81; seed 14 is soil 14, fertilizer 53, water 49
+    assert_eq!(almanac.convert(79, "seed", "water"), Ok(81));
+    assert_eq!(almanac.convert(14, "seed", "water"), Ok(49));
+    assert_eq!(almanac.convert(14, "soil", "fertilizer"), Ok(53));
+    assert_eq!(almanac.convert(5, "seed", "seed"), Ok(5));
+    assert_eq!(almanac.convert(1, "water", "seed"), Err(String::from("No maps lead from water to seed")));
+    assert_eq!(almanac.convert_ranges(&[Range {start: 79, end: 80}], "seed", "water"),
+               Ok(vec![Range {start: 81, end: 82}]));
+}
@@ -1,43 +1,69 @@
 use std::time::SystemTime;
+use num_bigint::BigUint;
+use num_integer::{Integer, Roots};
+use libaoc::config::Config;
+use crate::sheet::RaceSheet;
 
+mod sheet;
+
+// the races come from `input` (or `--sheet=path`), and --times/--records/--time/--record override them.
+// --brute_force also checks part 2 by trying every charge time, as long as the race is short enough.
 fn main() {
-    part1();
-    part2();
-    part3();
+    let config = Config::load();
+    let sheet = RaceSheet::read(&config.get("sheet", String::from("input")));
+    part1(&config, &sheet);
+    part2(&config, &sheet);
+    if config.flag("brute_force") {
+        part3(&config, &sheet);
+    }
 }
 
-fn part1() {
-    let times = vec![(41, 214), (96, 1789), (88, 1127), (94, 1055)];
+fn part1(config: &Config, sheet: &RaceSheet) {
+    let (sheet_times, sheet_records) = sheet.races.iter().cloned().unzip();
+    let times = config.get_list("times", sheet_times);
+    let distance_records = config.get_list("records", sheet_records);
+    assert_eq!(times.len(), distance_records.len(), "Every race needs both a time and a record");
     let mut score = 1;
-    for (time, distance_record) in times {
-        let wins = count_wins(time, distance_record);
+    for (time, distance_record) in times.into_iter().zip(distance_records) {
+        let wins = exact_wins(time, distance_record);
         score *= wins;
     }
     println!("Part 1: {score}");
 }
 
-fn part2() {
-    let time: i64 = 41968894;
-    let distance_record: i64 = 214178911271055;
+fn part2(config: &Config, sheet: &RaceSheet) {
+    let time: u128 = config.get("time", sheet.kerned.0);
+    let distance_record: u128 = config.get("record", sheet.kerned.1);
 
     let start = SystemTime::now();
-    let wins = count_wins(time, distance_record);
+    let wins = exact_wins(time, distance_record);
     let end = SystemTime::now();
     let duration = end.duration_since(start).unwrap().as_millis();
     println!("Part 2: {wins} in {duration} ms")
 }
 
-fn part3() {
-    let time: i64 = 41968894;
-    let distance_record: i64 = 214178911271055;
+// a billion charge times takes a few seconds to try
+const BRUTE_FORCE_LIMIT: u128 = 1_000_000_000;
+
+// check the exact answer against trying every charge time
+fn part3(config: &Config, sheet: &RaceSheet) {
+    let time: u128 = config.get("time", sheet.kerned.0);
+    if time > BRUTE_FORCE_LIMIT {
+        eprintln!("A {time} ms race is too long to try every charge time, the limit is {BRUTE_FORCE_LIMIT}");
+        return;
+    }
+    let distance_record: u128 = config.get("record", sheet.kerned.1);
+    let brute_record = i64::try_from(distance_record)
+        .unwrap_or_else(|_| panic!("A record of {distance_record} is too big to brute force"));
 
-    let wins = quadratic_your_wins(time, distance_record);
-    assert_eq!(wins, 30077773);
+    let wins = exact_wins(time, distance_record);
+    assert_eq!(wins, count_wins(time as i64, brute_record) as u128);
+    println!("Checked {wins} against every charge time");
 }
 
 fn count_wins(time: i64, distance_record: i64) -> i32 {
     let mut wins = 0;
-    for charge_time in 1..(time - 1) {
+    for charge_time in 1..time {
         let go_time = time - charge_time;
         let distance = charge_time * go_time;
         if distance > distance_record {
@@ -47,11 +73,106 @@ fn count_wins(time: i64, distance_record: i64) -> i32 {
     wins
 }
 
-fn quadratic_your_wins(time: i64, distance_record: i64) -> i32 {
-    let ftime = time as f64;
-    let fdistance_record = distance_record as f64;
-    let lower: f64 = (ftime - (ftime.powf(2.0) - 4.0 * fdistance_record).sqrt())/2.0;
-    let upper: f64 = (ftime + (ftime.powf(2.0) - 4.0 * fdistance_record).sqrt())/2.0;
+// how many charge times beat the record
+fn exact_wins(time: u128, distance_record: u128) -> u128 {
+    match winning_charge_times(time, distance_record) {
+        Some((first, last)) => last - first + 1,
+        None => 0
+    }
+}
+
+// The first and last charge times that beat the record, if any do.
+// Squaring the time only fits in a u128 while the time fits in a u64; past that, it's big integers.
+fn winning_charge_times(time: u128, distance_record: u128) -> Option<(u128, u128)> {
+    if time <= u64::MAX as u128 {
+        winning_interval(time, distance_record)
+    } else {
+        let (first, last) = winning_interval(BigUint::from(time), BigUint::from(distance_record))?;
+        // both are at most the time, so they fit back in a u128
+        Some((first.try_into().unwrap(), last.try_into().unwrap()))
+    }
+}
+
+// Charging for c out of t milliseconds wins when c * (t - c) > record, so between the roots of
+// c² - tc + record = 0, which are (t ± √(t² - 4 record)) / 2. An integer square root gets within one
+// of the first win without any floating point; then step onto it exactly. The wins are symmetric
+// around t / 2, so the last win mirrors the first.
+fn winning_interval<N>(time: N, distance_record: N) -> Option<(N, N)>
+where N: Integer + Roots + Clone + From<u8> {
+    let square = time.clone() * time.clone();
+    let needed = N::from(4) * distance_record.clone();
+    // with no real roots, or a single one, even the best charge time only ties the record
+    if square <= needed {
+        return None;
+    }
+    let root = (square - needed).sqrt();
+
+    let wins = |charge: &N| charge.clone() * (time.clone() - charge.clone()) > distance_record;
+    let half = time.clone() / N::from(2);
+    let mut first = (time.clone() - root) / N::from(2);
+    while first <= half && !wins(&first) {
+        first = first + N::one();
+    }
+    while first > N::zero() && wins(&(first.clone() - N::one())) {
+        first = first - N::one();
+    }
+    if !wins(&first) {
+        return None;
+    }
+    let last = time - first.clone();
+    Some((first, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use libaoc::difftest::{Differential, shrink_int};
+    use num_bigint::BigUint;
+    use crate::{count_wins, exact_wins, winning_charge_times, winning_interval};
+
+    #[test]
+    fn exact_matches_brute_force() {
+        Differential::new(|rng| {
+            let time = rng.range(1..200);
+            // records up to the best possible distance, so some races are unwinnable
+            let distance_record = rng.range(0..(time * time / 4 + 2));
+            (time, distance_record)
+        })
+            .shrink_with(|(time, distance_record)| {
+                let mut candidates: Vec<(i64, i64)> = shrink_int(*time).into_iter()
+                    .filter(|t| *t > 0)
+                    .map(|t| (t, *distance_record))
+                    .collect();
+                candidates.extend(shrink_int(*distance_record).into_iter().map(|d| (*time, d)));
+                candidates
+            })
+            .assert_agree(|(t, d)| count_wins(*t, *d) as u128,
+                          |(t, d)| exact_wins(*t as u128, *d as u128));
+    }
+
+    #[test]
+    fn exact_near_perfect_squares() {
+        // past 2^53, where f64 can't tell neighbouring charge times apart
+        let time: u128 = (1 << 60) + 12345;
+        for charge in [1u128, 3, 1 << 20, (1 << 59) - 7] {
+            // exactly tying the record at `charge` means the first win is one later
+            let record = charge * (time - charge);
+            assert_eq!(winning_charge_times(time, record), Some((charge + 1, time - charge - 1)));
+            assert_eq!(winning_charge_times(time, record - 1), Some((charge, time - charge)));
+        }
+        // an even time with the record at the very top: nothing beats it
+        assert_eq!(winning_charge_times(10, 25), None);
+        assert_eq!(winning_charge_times(10, 24), Some((5, 5)));
+    }
+
+    #[test]
+    fn big_integers() {
+        // too big to square in a u128
+        let time: u128 = (1 << 100) + 3;
+        let record: u128 = 5 * (time - 5);
+        assert_eq!(winning_charge_times(time, record), Some((6, time - 6)));
 
-    (upper - lower).ceil() as i32
+        let small = winning_interval(BigUint::from(71530u32), BigUint::from(940200u32))
+            .map(|(first, last)| (first.to_string(), last.to_string()));
+        assert_eq!(small, Some((String::from("14"), String::from("71516"))));
+    }
 }
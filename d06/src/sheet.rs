@@ -0,0 +1,81 @@
+use std::fs;
+
+// The puzzle input: a row of race times over a row of distance records.
+//
+//   Time:      7  15   30
+//   Distance:  9  40  200
+//
+// Read column by column, that's one race per column. Read with the bad kerning part 2 wants,
+// the digits on each row run together into a single race.
+#[derive(Debug, PartialEq)]
+pub struct RaceSheet {
+    // (time, record), one per column
+    pub races: Vec<(u128, u128)>,
+    pub kerned: (u128, u128),
+}
+
+impl RaceSheet {
+    pub fn read(filename: &str) -> RaceSheet {
+        let contents = fs::read_to_string(filename)
+            .unwrap_or_else(|e| panic!("Couldn't read race sheet {filename}: {e}"));
+        RaceSheet::parse(&contents)
+            .unwrap_or_else(|e| panic!("Bad race sheet {filename}: {e}"))
+    }
+
+    pub fn parse(contents: &str) -> Result<RaceSheet, String> {
+        let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+        let times = row(lines.next(), "Time")?;
+        let records = row(lines.next(), "Distance")?;
+        if let Some(extra) = lines.next() {
+            return Err(format!("Expected just Time and Distance lines, also got `{extra}`"));
+        }
+        if times.len() != records.len() {
+            return Err(format!("{} times but {} distances", times.len(), records.len()));
+        }
+        if times.is_empty() {
+            return Err(String::from("No races"));
+        }
+
+        let number = |digits: &str| digits.parse::<u128>().map_err(|_| format!("{digits} is too big"));
+        let races = times.iter().zip(records.iter())
+            .map(|(time, record)| Ok((number(time)?, number(record)?)))
+            .collect::<Result<Vec<(u128, u128)>, String>>()?;
+        let kerned = (number(&times.concat())?, number(&records.concat())?);
+
+        Ok(RaceSheet {races, kerned})
+    }
+}
+
+// the numbers after `label:`, still as digits so they can be run together
+fn row(line: Option<&str>, label: &str) -> Result<Vec<String>, String> {
+    let line = line.ok_or(format!("Missing {label} line"))?;
+    let (found, numbers) = line.split_once(':')
+        .ok_or(format!("Expected `{label}:` at the start of `{line}`"))?;
+    if found.trim() != label {
+        return Err(format!("Expected `{label}:`, got `{found}:`"));
+    }
+    numbers.split_whitespace()
+        .map(|n| if n.chars().all(|ch| ch.is_ascii_digit()) {
+            Ok(String::from(n))
+        } else {
+            Err(format!("{n} on the {label} line isn't a number"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let sheet = RaceSheet::parse("Time:      7  15   30\nDistance:  9  40  200\n").unwrap();
+        assert_eq!(sheet.races, vec![(7, 9), (15, 40), (30, 200)]);
+        assert_eq!(sheet.kerned, (71530, 940200));
+
+        assert_eq!(RaceSheet::parse("Time: 7 15\nDistance: 9\n"), Err(String::from("2 times but 1 distances")));
+        assert!(RaceSheet::parse("Distance: 9\nTime: 7\n").is_err());
+        assert!(RaceSheet::parse("Time: 7\n").is_err());
+        assert!(RaceSheet::parse("Time: 7x\nDistance: 9\n").is_err());
+    }
+}
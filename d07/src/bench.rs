@@ -0,0 +1,56 @@
+use std::cmp::Ordering;
+use std::time::Instant;
+use libaoc::difftest::Rng;
+use crate::Hand;
+use crate::rules::RuleSet;
+
+// `count` lines of random hands with random bids, in the input's format
+pub fn generate(rules: &RuleSet, count: usize, seed: u64) -> Vec<String> {
+    let mut rng = Rng::new(seed);
+    let size = rules.hand_size.unwrap_or(5);
+    (0..count).map(|_| {
+        let cards: String = (0..size).map(|_| {
+            let card = rules.order[rng.range(0..rules.order.len() as i64) as usize];
+            let face = card.to_char();
+            if rules.suited {
+                format!("{face}{}", ['s', 'h', 'd', 'c'][rng.range(0..4) as usize])
+            } else {
+                face.to_string()
+            }
+        }).collect();
+        format!("{cards} {}", rng.range(1..1000))
+    }).collect()
+}
+
+// How sorting used to work: classify both hands on every comparison.
+pub fn compare_uncached(rules: &RuleSet, a: &Hand, b: &Hand) -> Ordering {
+    let a_type = rules.classify(&a.cards, &a.suits);
+    let b_type = rules.classify(&b.cards, &b.suits);
+    rules.category_rank(&a_type).cmp(&rules.category_rank(&b_type))
+        .then_with(|| a.cards.iter()
+            .zip(b.cards.iter())
+            .map(|(ac, bc)| rules.strength(ac).cmp(&rules.strength(bc)))
+            .find(|order| order.is_ne())
+            .unwrap_or(Ordering::Equal))
+}
+
+// time parsing and sorting `count` generated hands, with and without the cached keys
+pub fn run(rules: &RuleSet, count: usize) {
+    let lines = generate(rules, count, 7);
+
+    let begin = Instant::now();
+    let mut hands: Vec<Hand> = lines.iter().map(|line| Hand::parse(line, rules)).collect();
+    println!("Parsed {count} hands in {} ms", begin.elapsed().as_millis());
+
+    let mut uncached: Vec<&Hand> = hands.iter().collect();
+    let begin = Instant::now();
+    uncached.sort_by(|a, b| compare_uncached(rules, a, b));
+    println!("Sorted classifying every comparison in {} ms", begin.elapsed().as_millis());
+    let uncached_bids: Vec<u64> = uncached.iter().map(|hand| hand.bid).collect();
+
+    let begin = Instant::now();
+    hands.sort();
+    println!("Sorted by cached keys in {} ms", begin.elapsed().as_millis());
+
+    assert!(hands.iter().map(|hand| hand.bid).eq(uncached_bids), "The two sorts should agree");
+}
@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use itertools::Itertools;
+use crate::{Card, Hand, HandType};
+use crate::rules::RuleSet;
+
+const SUITS: [char; 4] = ['s', 'h', 'd', 'c'];
+
+// a hand's cards and their suits, the way Hand keeps them
+type Dealt = (Vec<Card>, Vec<char>);
+
+// cards the way the input writes them, like `QJJQ2` or `Qh2s`
+pub fn show(cards: &[Card], suits: &[char]) -> String {
+    cards.iter().enumerate()
+        .map(|(idx, card)| match suits.get(idx) {
+            Some(suit) => format!("{}{suit}", card.to_char()),
+            None => card.to_char().to_string(),
+        })
+        .collect()
+}
+
+// What the wild cards in a hand stand in for to make its type, trying stronger cards first.
+// None if there aren't any wild cards.
+pub fn best_substitution(rules: &RuleSet, cards: &[Card], suits: &[char]) -> Result<Option<Dealt>, String> {
+    let wild_positions: Vec<usize> = (0..cards.len()).filter(|idx| rules.is_wild(&cards[*idx])).collect();
+    if wild_positions.is_empty() {
+        return Ok(None);
+    }
+    let target = rules.classify(cards, suits);
+    let tame = RuleSet { wild: vec![], ..rules.clone() };
+    let faces: Vec<Card> = rules.order.iter().rev().filter(|card| !rules.is_wild(card)).cloned().collect();
+    let stand_ins: Vec<(Card, Option<char>)> = if rules.suited {
+        faces.iter().cartesian_product(SUITS).map(|(card, suit)| (*card, Some(suit))).collect()
+    } else {
+        faces.iter().map(|card| (*card, None)).collect()
+    };
+
+    stand_ins.into_iter()
+        .combinations_with_replacement(wild_positions.len())
+        .map(|picks| {
+            let (mut cards, mut suits) = (cards.to_vec(), suits.to_vec());
+            for (idx, (card, suit)) in wild_positions.iter().zip(picks) {
+                cards[*idx] = card;
+                if let Some(suit) = suit {
+                    suits[*idx] = suit;
+                }
+            }
+            (cards, suits)
+        })
+        .find(|(cards, suits)| tame.classify(cards, suits) == target)
+        .map(Some)
+        .ok_or_else(|| format!("Nothing the wild cards in {} stand for makes a {target:?}", show(cards, suits)))
+}
+
+// "QJJQ2 → QQQQ2 = FourOfKind", or just "KK677 = TwoPair" without any wild cards
+pub fn explain(rules: &RuleSet, hand: &Hand) -> Result<String, String> {
+    let dealt = show(&hand.cards, &hand.suits);
+    Ok(match best_substitution(rules, &hand.cards, &hand.suits)? {
+        Some((cards, suits)) => format!("{dealt} → {} = {:?}", show(&cards, &suits), hand.hand_type),
+        None => format!("{dealt} = {:?}", hand.hand_type),
+    })
+}
+
+// `copies` of every card in the rules' order, spread over the four suits when the rules have them
+pub fn deck(rules: &RuleSet, copies: usize) -> Vec<(Card, Option<char>)> {
+    rules.order.iter()
+        .flat_map(|card| (0..copies).map(move |copy| {
+            (*card, if rules.suited { Some(SUITS[copy % SUITS.len()]) } else { None })
+        }))
+        .collect()
+}
+
+// How likely each hand type is once a partial hand is filled up to `size` cards from what's left of the deck,
+// going through every way the rest could be drawn.
+pub fn distribution(rules: &RuleSet, cards: &[Card], suits: &[char], deck: &[(Card, Option<char>)], size: usize)
+    -> Result<BTreeMap<HandType, f64>, String> {
+    if let Some(hand_size) = rules.hand_size.filter(|hand_size| *hand_size != size) {
+        return Err(format!("These rules only rank hands of {hand_size} cards"));
+    }
+    if cards.len() > size {
+        return Err(format!("{} already has more than {size} cards", show(cards, suits)));
+    }
+    let mut left = deck.to_vec();
+    for (idx, card) in cards.iter().enumerate() {
+        let wanted = (*card, suits.get(idx).cloned());
+        let position = left.iter().position(|c| *c == wanted)
+            .ok_or_else(|| format!("{} isn't left in the deck", show(&[*card], suits.get(idx..idx + 1).unwrap_or(&[]))))?;
+        left.remove(position);
+    }
+
+    let mut counts: BTreeMap<HandType, u64> = BTreeMap::new();
+    let mut total = 0;
+    for drawn in left.iter().combinations(size - cards.len()) {
+        let mut hand_cards = cards.to_vec();
+        let mut hand_suits = suits.to_vec();
+        for (card, suit) in drawn {
+            hand_cards.push(*card);
+            hand_suits.extend(suit);
+        }
+        *counts.entry(rules.classify(&hand_cards, &hand_suits)).or_default() += 1;
+        total += 1;
+    }
+    Ok(counts.into_iter().map(|(hand_type, count)| (hand_type, count as f64 / total as f64)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HandType::*;
+    use crate::parse_cards;
+
+    fn explained(rules: &RuleSet, hand: &str) -> String {
+        explain(rules, &Hand::parse(&format!("{hand} 1"), rules)).unwrap()
+    }
+
+    #[test]
+    fn substitutions() {
+        let rules = RuleSet::jokers_wild();
+        assert_eq!(explained(&rules, "QJJQ2"), "QJJQ2 → QQQQ2 = FourOfKind");
+        assert_eq!(explained(&rules, "JJJJJ"), "JJJJJ → AAAAA = FiveOfKind");
+        assert_eq!(explained(&rules, "2233J"), "2233J → 22333 = FullHouse");
+        assert_eq!(explained(&rules, "KK677"), "KK677 = TwoPair");
+
+        let wild_poker = RuleSet { wild: vec![Card::C2], ..RuleSet::poker() };
+        assert_eq!(explained(&wild_poker, "9hThJh2sKh"), "9hThJh2sKh → 9hThJhQhKh = StraightFlush");
+    }
+
+    #[test]
+    fn probabilities() {
+        let rules = RuleSet::camel_cards();
+        let deck = deck(&rules, 4);
+        assert_eq!(deck.len(), 52);
+
+        let (cards, suits) = parse_cards("AAA", &rules);
+        let odds = distribution(&rules, &cards, &suits, &deck, 5).unwrap();
+        // one ace left among 49 cards, so 48 of the 1176 draws make four of a kind,
+        // and 12 * 6 pairs make a full house
+        assert_eq!(odds, BTreeMap::from([
+            (ThreeOfKind, 1056.0 / 1176.0),
+            (FullHouse, 72.0 / 1176.0),
+            (FourOfKind, 48.0 / 1176.0),
+        ]));
+
+        let (cards, suits) = parse_cards("KK677", &rules);
+        assert_eq!(distribution(&rules, &cards, &suits, &deck, 5), Ok(BTreeMap::from([(TwoPair, 1.0)])));
+    }
+
+    #[test]
+    fn impossible_requests() {
+        let rules = RuleSet::camel_cards();
+        let (cards, suits) = parse_cards("AAA", &rules);
+        assert_eq!(distribution(&rules, &cards, &suits, &deck(&rules, 2), 5), Err(String::from("A isn't left in the deck")));
+        assert!(distribution(&rules, &cards, &suits, &deck(&rules, 4), 7).is_err());
+
+        // when every card is wild there's nothing for them to stand in for
+        let all_wild = RuleSet { hand_size: None, order: vec![Card::CJ], ..RuleSet::jokers_wild() };
+        let (cards, suits) = parse_cards("JJ", &all_wild);
+        assert_eq!(best_substitution(&all_wild, &cards, &suits),
+                   Err(String::from("Nothing the wild cards in JJ stand for makes a SinglePair")));
+    }
+}
@@ -1,72 +1,75 @@
 use std::cmp::Ordering;
 use std::fs;
 use itertools::Itertools;
-use crate::HandType::{FiveOfKind, FourOfKind, FullHouse, HighCard, SinglePair, ThreeOfKind, TwoPair};
+use libaoc::config::Config;
+use crate::rules::RuleSet;
+
+mod bench;
+mod explain;
+mod rules;
 
 #[derive(Eq, PartialEq, Hash, Debug)]
 struct Hand {
     bid: u64,
     cards: Vec<Card>,
+    // one per card when the rules have suits, otherwise empty
+    suits: Vec<char>,
+    hand_type: HandType,
+    // how the hand ranks under its rules: the category's rank, then each card's strength
+    ranks: (usize, Vec<usize>),
+    // the same ranks packed into one number, for hands short enough to fit,
+    // so sorting is mostly one comparison per pair of hands
+    key: Option<u128>,
 }
 
-#[derive(Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Clone, Copy)]
 enum HandType {
-    HighCard, SinglePair, TwoPair, ThreeOfKind, FullHouse, FourOfKind, FiveOfKind
+    HighCard, SinglePair, TwoPair, ThreeOfKind, Straight, Flush, FullHouse, FourOfKind, StraightFlush, FiveOfKind
 }
 
-impl Hand {
-    fn rank_hand(&self) -> HandType {
-        // group the cards by their identity
-        let mut card_groups = self.cards.iter()
-            .into_group_map_by(|c| *c);
-
-        // set aside & count the jokers
-        let joker_count = card_groups.remove(&Card::JOKER)
-            .map(|jokers| jokers.len())
-            .unwrap_or(0);
-
-        // for each card group, count the size, then order the sizes high-to-low
-        let mut card_sizes = card_groups.values()
-            .map(|g| g.len())
-            .sorted()
-            .rev()
-            .collect_vec();
-
-        if card_sizes.is_empty() {
-            // if we had all jokers, that's a 5 of a kind!
-            card_sizes.push(joker_count);
-        } else {
-            // otherwise, add the jokers to whatever the biggest group was
-            card_sizes[0] = card_sizes[0] + joker_count;
-        }
+// a hand's key has 4 bits for its category, then 4 bits per card
+const KEY_CARDS: usize = 31;
 
-        return if card_sizes[0] == 1 {
-            HighCard
-        } else if card_sizes[0] == 2 && card_sizes[1] == 1 {
-            SinglePair
-        } else if card_sizes[0] == 2 && card_sizes[1] == 2 {
-            TwoPair
-        } else if card_sizes[0] == 3  && card_sizes[1] == 1 {
-            ThreeOfKind
-        } else if card_sizes[0] == 3 && card_sizes[1] == 2 {
-            FullHouse
-        } else if card_sizes[0] == 4 {
-            FourOfKind
-        } else if card_sizes[0] == 5 {
-            FiveOfKind
-        } else {
-            panic!("Not sure what kind of hand this is");
-        }
+impl Hand {
+    // a hand of camel cards
+    #[cfg(test)]
+    fn from_string(line: &str) -> Hand {
+        Hand::parse(line, &RuleSet::camel_cards())
     }
 
-    fn from_string(line: &str) -> Hand {
+    fn parse(line: &str, rules: &RuleSet) -> Hand {
         let parts: Vec<&str> = line.split_whitespace().collect();
-        let cards: Vec<Card> = parts[0].chars()
-            .map(|c| Card::from_char(c).unwrap())
-            .collect();
+        let (cards, suits) = parse_cards(parts[0], rules);
         let bid: u64 = parts[1].parse().unwrap();
+        let hand_type = rules.classify(&cards, &suits);
+        let ranks = (rules.category_rank(&hand_type), cards.iter().map(|card| rules.strength(card)).collect());
+        let key = Hand::key(rules, &ranks);
+
+        Hand { bid, cards, suits, hand_type, ranks, key }
+    }
+
+    // The ranks packed high to low, so comparing keys is the same as comparing the hands,
+    // or None when the hand has too many cards to fit.
+    fn key(rules: &RuleSet, (category, strengths): &(usize, Vec<usize>)) -> Option<u128> {
+        if strengths.len() > KEY_CARDS {
+            return None;
+        }
+        assert!(rules.order.len() <= 16 && rules.categories.len() <= 16, "Rules need at most 16 cards and categories");
+        let packed = strengths.iter().fold(*category as u128, |key, strength| key << 4 | *strength as u128);
+        Some(packed << (4 * (KEY_CARDS - strengths.len())))
+    }
+}
 
-        Hand { bid, cards }
+// `QJJQ2`, or `Qh2s...` when the rules have suits
+fn parse_cards(text: &str, rules: &RuleSet) -> (Vec<Card>, Vec<char>) {
+    if rules.suited {
+        assert!(text.chars().count().is_multiple_of(2), "Every card in {text} needs a suit after it");
+        text.chars()
+            .tuples()
+            .map(|(c, suit)| (Card::from_char(c).unwrap(), suit))
+            .unzip()
+    } else {
+        (text.chars().map(|c| Card::from_char(c).unwrap()).collect(), vec![])
     }
 }
 
@@ -77,25 +80,19 @@ impl PartialOrd<Self> for Hand {
 }
 
 impl Ord for Hand {
+    // Keys pad short hands with zeros, so equal keys can still be hands of different lengths,
+    // and those go by the ranks too.
     fn cmp(&self, other: &Self) -> Ordering {
-        let a_matches = self.rank_hand();
-        let b_matches = other.rank_hand();
-
-        return if a_matches != b_matches {
-            a_matches.cmp(&b_matches)
-        } else {
-            self.cards.iter()
-                .zip(other.cards.iter())
-                .find(|(ac, bc)| ac != bc)
-                .map(|(ac, bc)| ac.cmp(bc))
-                .unwrap_or(Ordering::Equal)
-        };
+        match (self.key, other.key) {
+            (Some(a), Some(b)) if a != b => a.cmp(&b),
+            _ => self.ranks.cmp(&other.ranks),
+        }
     }
 }
 
-#[derive(PartialOrd, Ord, PartialEq, Eq, Debug, Hash)]
+#[derive(PartialOrd, Ord, PartialEq, Eq, Debug, Hash, Clone, Copy)]
 enum Card {
-    JOKER, C2, C3, C4, C5, C6, C7, C8, C9, CT, CJ, CQ, CK, CA,
+    C2, C3, C4, C5, C6, C7, C8, C9, CT, CJ, CQ, CK, CA,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -104,7 +101,6 @@ struct CardParseError;
 impl Card {
     fn from_char(input: char) -> Result<Self, CardParseError> {
         match input {
-            'j' => Ok(Card::JOKER),
             '2' => Ok(Card::C2),
             '3' => Ok(Card::C3),
             '4' => Ok(Card::C4),
@@ -118,18 +114,54 @@ impl Card {
             'Q' => Ok(Card::CQ),
             'K' => Ok(Card::CK),
             'A' => Ok(Card::CA),
-            __ => Err(CardParseError)
+            _ => Err(CardParseError)
         }
     }
+
+    fn to_char(self) -> char {
+        "23456789TJQKA".chars().nth(self as usize).unwrap()
+    }
 }
 
+// --rules=camel|jokers|poker also scores `--hands=path` (input) under those rules,
+// with --any_size to allow hands of any length.
+// --bench=N times sorting N generated hands instead.
 fn main() {
+    let config = Config::load();
+    match config.positional().first().map(String::as_str) {
+        Some("explain") => return explain(&config),
+        Some("analyze") => return analyze(&config),
+        _ => {}
+    }
+    let name = config.get("rules", String::new());
+    let bench: usize = config.get("bench", 0);
+    if bench > 0 {
+        let rules = if name.is_empty() { RuleSet::camel_cards() } else { RuleSet::by_name(&name) };
+        bench::run(&rules, bench);
+        return;
+    }
+
     part1();
     part2();
+
+    if !name.is_empty() {
+        let mut rules = RuleSet::by_name(&name);
+        if config.flag("any_size") {
+            rules.hand_size = None;
+        }
+        let mut hands = read_hands(&config.get("hands", String::from("input")), &rules);
+        hands.sort();
+
+        let sum_winnings: u64 = hands.iter().enumerate()
+            .map(|(rank, hand)| hand.bid * ((rank + 1) as u64))
+            .sum();
+
+        println!("Winnings with {name} rules: {sum_winnings}");
+    }
 }
 
 fn part1() {
-    let mut hands = read_hands("input", false);
+    let mut hands = read_hands("input", &RuleSet::camel_cards());
     hands.sort();
 
     let sum_winnings: u64 = hands.iter().enumerate()
@@ -140,7 +172,7 @@ fn part1() {
 }
 
 fn part2() {
-    let mut hands = read_hands("input", true);
+    let mut hands = read_hands("input", &RuleSet::jokers_wild());
     hands.sort();
 
     let sum_winnings: u64 = hands.iter().enumerate()
@@ -150,18 +182,54 @@ fn part2() {
     println!("Part 2: {sum_winnings}");
 }
 
-fn read_hands(input_file: &str, jokers_trick: bool) -> Vec<Hand> {
+// `d07 explain --rules=jokers --hands=input`
+//
+// Shows what the wild cards in each hand stand in for, like `QJJQ2 → QQQQ2 = FourOfKind`.
+fn explain(config: &Config) {
+    let rules = RuleSet::by_name(&config.get("rules", String::from("jokers")));
+    for hand in read_hands(&config.get("hands", String::from("input")), &rules) {
+        if hand.cards.iter().any(|card| rules.is_wild(card)) {
+            match explain::explain(&rules, &hand) {
+                Ok(explained) => println!("{explained}"),
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+    }
+}
+
+// `d07 analyze --partial=QJ --rules=jokers --copies=4 --size=5`
+//
+//   --partial  the cards already in the hand
+//   --rules    camel, jokers or poker (camel)
+//   --copies   how many of each card are in the deck, spread over the suits (4)
+//   --size     how many cards a full hand has (5, or the rules' hand size)
+fn analyze(config: &Config) {
+    let rules = RuleSet::by_name(&config.get("rules", String::from("camel")));
+    let size = config.get("size", rules.hand_size.unwrap_or(5));
+    // the analysis decides how big the hands are, whatever the rules say
+    let rules = RuleSet { hand_size: Some(size), ..rules };
+    let (cards, suits) = parse_cards(&config.get("partial", String::new()), &rules);
+    let deck = explain::deck(&rules, config.get("copies", 4));
+
+    let odds = match explain::distribution(&rules, &cards, &suits, &deck, size) {
+        Ok(odds) => odds,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    println!("Filling {} up to {size} cards from a deck of {}:", explain::show(&cards, &suits), deck.len());
+    for (hand_type, chance) in odds.iter().rev() {
+        println!("{:>14} {:>8.4}%", format!("{hand_type:?}"), chance * 100.0);
+    }
+}
+
+fn read_hands(input_file: &str, rules: &RuleSet) -> Vec<Hand> {
+    rules.check().unwrap_or_else(|e| panic!("Can't rank hands with these rules: {e}"));
     fs::read_to_string(input_file)
         .unwrap()
         .lines()
-        .map(|line| {
-            if jokers_trick {
-                line.replace("J", "j")
-            } else {
-                String::from(line)
-            }
-        })
-        .map(|line| Hand::from_string(&line))
+        .map(|line| Hand::parse(line, rules))
         .collect()
 }
 
@@ -170,7 +238,10 @@ fn read_hands(input_file: &str, jokers_trick: bool) -> Vec<Hand> {
 #[cfg(test)]
 mod tests {
     use crate::Card::{C4, CA, CT};
+    use crate::KEY_CARDS;
     use crate::{Hand, read_hands};
+    use crate::rules::RuleSet;
+    use crate::bench::{compare_uncached, generate};
 
     #[test]
     fn line_to_hand() {
@@ -221,7 +292,7 @@ mod tests {
 
     #[test]
     fn part1_ex() {
-        let mut hands = read_hands("example", false);
+        let mut hands = read_hands("example", &RuleSet::camel_cards());
         hands.sort();
 
         let sum_winnings: u64 = hands.iter().enumerate()
@@ -233,7 +304,7 @@ mod tests {
 
     #[test]
     fn part2_ex() {
-        let mut hands = read_hands("example", true);
+        let mut hands = read_hands("example", &RuleSet::jokers_wild());
         hands.sort();
 
         let sum_winnings: u64 = hands.iter().enumerate()
@@ -242,4 +313,40 @@ mod tests {
 
         assert_eq!(sum_winnings, 5905);
     }
+
+    #[test]
+    fn sorts_hands_too_long_for_a_key() {
+        let rules = RuleSet { hand_size: None, ..RuleSet::camel_cards() };
+        let hand = |cards: &str| Hand::parse(&format!("{cards} 1"), &rules);
+        // 32 cards each: eight of each of four faces, and a full house of two or three of every face
+        let low = hand(&"2345".repeat(8));
+        let high = hand(&"2346".repeat(8));
+        let all_twos = hand(&"2".repeat(32));
+        let full_house = hand(&format!("{}234567", "23456789TJQKA".repeat(2)));
+        let short = hand("22222");
+        assert!(low.cards.len() > KEY_CARDS && low.key.is_none() && short.key.is_some());
+
+        let mut hands = vec![&high, &short, &low, &full_house, &all_twos];
+        hands.sort();
+        assert_eq!(hands, vec![&full_house, &short, &all_twos, &low, &high]);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs a suit")]
+    fn card_without_a_suit() {
+        Hand::parse("AhKhQhJhT 1", &RuleSet::poker());
+    }
+
+    #[test]
+    fn keys_sort_like_comparisons() {
+        for rules in [RuleSet::camel_cards(), RuleSet::jokers_wild(), RuleSet::poker()] {
+            let hands: Vec<Hand> = generate(&rules, 2000, 3).iter()
+                .map(|line| Hand::parse(line, &rules))
+                .collect();
+            for pair in hands.windows(2) {
+                assert_eq!(pair[0].cmp(&pair[1]), compare_uncached(&rules, &pair[0], &pair[1]),
+                           "{:?} vs {:?}", pair[0].cards, pair[1].cards);
+            }
+        }
+    }
 }
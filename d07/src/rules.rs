@@ -0,0 +1,252 @@
+use itertools::Itertools;
+use crate::{Card, HandType};
+use crate::HandType::*;
+
+// How a game is played: what a hand looks like, how the cards rank, which cards are wild,
+// and which hand types count (weakest first).
+//
+// A hand's type is the strongest one in `categories` it can make, with wild cards standing in
+// for whatever helps most. Hands of the same type are compared card by card, in the order dealt.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RuleSet {
+    // None for any number of cards
+    pub hand_size: Option<usize>,
+    // weakest first
+    pub order: Vec<Card>,
+    pub wild: Vec<Card>,
+    // weakest first
+    pub categories: Vec<HandType>,
+    // cards are written with a suit after them, like `As`
+    pub suited: bool,
+}
+
+const FACES: [Card; 13] = [
+    Card::C2, Card::C3, Card::C4, Card::C5, Card::C6, Card::C7, Card::C8, Card::C9,
+    Card::CT, Card::CJ, Card::CQ, Card::CK, Card::CA
+];
+
+impl RuleSet {
+    pub fn camel_cards() -> RuleSet {
+        RuleSet {
+            hand_size: Some(5),
+            order: FACES.to_vec(),
+            wild: vec![],
+            categories: vec![HighCard, SinglePair, TwoPair, ThreeOfKind, FullHouse, FourOfKind, FiveOfKind],
+            suited: false,
+        }
+    }
+
+    // part 2: J is wild, but the weakest card when breaking ties
+    pub fn jokers_wild() -> RuleSet {
+        let mut order = FACES.to_vec();
+        order.retain(|card| *card != Card::CJ);
+        order.insert(0, Card::CJ);
+        RuleSet { order, wild: vec![Card::CJ], ..RuleSet::camel_cards() }
+    }
+
+    // five card poker, with suits
+    pub fn poker() -> RuleSet {
+        RuleSet {
+            categories: vec![HighCard, SinglePair, TwoPair, ThreeOfKind, Straight, Flush, FullHouse,
+                             FourOfKind, StraightFlush],
+            suited: true,
+            ..RuleSet::camel_cards()
+        }
+    }
+
+    pub fn by_name(name: &str) -> RuleSet {
+        let rules = match name {
+            "camel" => RuleSet::camel_cards(),
+            "jokers" => RuleSet::jokers_wild(),
+            "poker" => RuleSet::poker(),
+            other => panic!("Unknown rules {other}, expected camel, jokers or poker"),
+        };
+        rules.check().unwrap_or_else(|e| panic!("The {name} rules don't work: {e}"));
+        rules
+    }
+
+    // Whether every hand can be ranked: classify falls back on HighCard when nothing else fits,
+    // so it has to be one of the categories, and the packed sort keys only have room for 16
+    // cards and categories.
+    pub fn check(&self) -> Result<(), String> {
+        if !self.categories.contains(&HighCard) {
+            return Err(String::from("HighCard has to be one of the categories, for hands that make nothing else"));
+        }
+        if let Some(twice) = self.categories.iter().duplicates().next() {
+            return Err(format!("{twice:?} is in the categories more than once"));
+        }
+        if let Some(twice) = self.order.iter().duplicates().next() {
+            return Err(format!("{twice:?} is in the card order more than once"));
+        }
+        if self.order.len() > 16 || self.categories.len() > 16 {
+            return Err(String::from("There can be at most 16 cards and 16 categories"));
+        }
+        Ok(())
+    }
+
+    // how strong a card is when breaking ties, 0 being the weakest
+    pub fn strength(&self, card: &Card) -> usize {
+        self.order.iter()
+            .position(|c| c == card)
+            .unwrap_or_else(|| panic!("{card:?} isn't in this game's deck"))
+    }
+
+    pub fn is_wild(&self, card: &Card) -> bool {
+        self.wild.contains(card)
+    }
+
+    // where a hand type ranks in this game, which `check` makes sure every classified hand has
+    pub fn category_rank(&self, hand_type: &HandType) -> usize {
+        self.categories.iter()
+            .position(|c| c == hand_type)
+            .unwrap_or_else(|| panic!("{hand_type:?} isn't one of this game's categories (see RuleSet::check)"))
+    }
+
+    pub fn classify(&self, cards: &[Card], suits: &[char]) -> HandType {
+        if let Some(size) = self.hand_size {
+            assert_eq!(cards.len(), size, "Hands should have {size} cards");
+        }
+        let shape = Shape::of(self, cards);
+        self.categories.iter()
+            .rev()
+            .find(|category| self.makes(category, &shape, cards, suits))
+            .cloned()
+            .unwrap_or(HighCard)
+    }
+
+    fn makes(&self, category: &HandType, shape: &Shape, cards: &[Card], suits: &[char]) -> bool {
+        let wilds = shape.wilds;
+        match category {
+            HighCard => true,
+            SinglePair => shape.short(2, 0) <= wilds,
+            TwoPair => shape.short(2, 2) <= wilds,
+            ThreeOfKind => shape.short(3, 0) <= wilds,
+            FullHouse => shape.short(3, 2) <= wilds,
+            FourOfKind => shape.short(4, 0) <= wilds,
+            FiveOfKind => shape.short(5, 0) <= wilds,
+            Straight => self.has_straight(cards.iter().collect(), wilds),
+            Flush => self.has_flush(cards, suits, wilds).is_some(),
+            StraightFlush => self.suits_in(suits).iter().any(|suit| {
+                let suited: Vec<&Card> = cards.iter().zip(suits)
+                    .filter(|(card, s)| *s == suit && !self.is_wild(card))
+                    .map(|(card, _)| card)
+                    .collect();
+                suited.len() + wilds >= 5 && self.has_straight(suited, wilds)
+            }),
+        }
+    }
+
+    fn suits_in(&self, suits: &[char]) -> Vec<char> {
+        suits.iter().cloned().unique().collect()
+    }
+
+    fn has_flush(&self, cards: &[Card], suits: &[char], wilds: usize) -> Option<char> {
+        self.suits_in(suits).into_iter().find(|suit| {
+            let count = cards.iter().zip(suits)
+                .filter(|(card, s)| *s == suit && !self.is_wild(card))
+                .count();
+            count + wilds >= 5
+        })
+    }
+
+    // Five cards in a row in this game's order, with wild cards filling gaps.
+    // An ace can also come before the 2, as in A2345.
+    fn has_straight(&self, cards: Vec<&Card>, wilds: usize) -> bool {
+        let mut positions: Vec<i64> = cards.iter()
+            .filter(|card| !self.is_wild(card))
+            .map(|card| self.strength(card) as i64)
+            .collect();
+        if cards.contains(&&Card::CA) && self.order.first() == Some(&Card::C2) {
+            positions.push(-1);
+        }
+        let top = self.order.len() as i64;
+        (-1..=top - 5).any(|low| {
+            let missing = (low..low + 5).filter(|p| !positions.contains(p)).count();
+            missing <= wilds
+        })
+    }
+}
+
+// the wild cards, and the biggest two groups of matching cards leaving the wild ones out
+struct Shape {
+    wilds: usize,
+    first: usize,
+    second: usize,
+}
+
+impl Shape {
+    fn of(rules: &RuleSet, cards: &[Card]) -> Shape {
+        let mut counts = [0; 16];
+        let mut wilds = 0;
+        for card in cards {
+            if rules.is_wild(card) {
+                wilds += 1;
+            } else {
+                counts[*card as usize] += 1;
+            }
+        }
+        let (mut first, mut second) = (0, 0);
+        for count in counts {
+            if count > first {
+                (first, second) = (count, first);
+            } else if count > second {
+                second = count;
+            }
+        }
+        Shape { wilds, first, second }
+    }
+
+    // how many wild cards it takes to bring the top two groups up to these sizes
+    fn short(&self, a: usize, b: usize) -> usize {
+        a.saturating_sub(self.first) + b.saturating_sub(self.second)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Hand;
+
+    fn classify(rules: &RuleSet, hand: &str) -> HandType {
+        Hand::parse(&format!("{hand} 1"), rules).hand_type
+    }
+
+    #[test]
+    fn jokers() {
+        let rules = RuleSet::jokers_wild();
+        assert_eq!(classify(&rules, "QJJQ2"), FourOfKind);
+        assert_eq!(classify(&rules, "JJJJJ"), FiveOfKind);
+        assert_eq!(classify(&rules, "T55J5"), FourOfKind);
+        assert_eq!(classify(&rules, "2345J"), SinglePair);
+        assert_eq!(classify(&rules, "2233J"), FullHouse);
+        assert_eq!(classify(&RuleSet::camel_cards(), "QJJQ2"), TwoPair);
+    }
+
+    #[test]
+    fn poker_hands() {
+        let rules = RuleSet::poker();
+        assert_eq!(classify(&rules, "2h3d4s5c6h"), Straight);
+        assert_eq!(classify(&rules, "Ah2d3s4c5h"), Straight);
+        assert_eq!(classify(&rules, "Kh2h9h4h5h"), Flush);
+        assert_eq!(classify(&rules, "9sTsJsQsKs"), StraightFlush);
+        assert_eq!(classify(&rules, "9s9h9d2s2c"), FullHouse);
+        assert_eq!(classify(&rules, "QhKhAh2h3d"), HighCard);
+    }
+
+    #[test]
+    fn checking_rules() {
+        assert_eq!(RuleSet::poker().check(), Ok(()));
+        let no_high_card = RuleSet { categories: vec![SinglePair, TwoPair], ..RuleSet::camel_cards() };
+        assert!(no_high_card.check().is_err());
+        let twice = RuleSet { categories: vec![HighCard, SinglePair, SinglePair], ..RuleSet::camel_cards() };
+        assert!(twice.check().is_err());
+    }
+
+    #[test]
+    fn longer_hands() {
+        let rules = RuleSet { hand_size: None, ..RuleSet::jokers_wild() };
+        assert_eq!(classify(&rules, "2233445"), TwoPair);
+        assert_eq!(classify(&rules, "22J"), ThreeOfKind);
+        assert_eq!(classify(&rules, "AAAAAAA"), FiveOfKind);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use crate::Node;
+
+// Where a walk from one start ends up. The walk's state is (node, position in the instructions),
+// so once a state comes around again the walk repeats forever from there.
+#[derive(Debug, PartialEq)]
+pub struct Cycle {
+    pub start: String,
+    // steps before the walk reaches the state it keeps coming back to
+    pub lead_in: u64,
+    pub length: u64,
+    // steps where the walk is on an end node before the loop starts, which never come round again
+    pub lead_in_ends: Vec<u64>,
+    // steps in [lead_in, lead_in + length) where the walk is on an end node, which repeat every `length` steps
+    pub offsets: Vec<u64>,
+}
+
+impl Cycle {
+    pub fn find(instructions: &str, nodes: &HashMap<String, Node>, start: &str, end_pattern: &str) -> Cycle {
+        let directions: Vec<char> = instructions.trim().chars().collect();
+        let mut seen: HashMap<(&str, usize), u64> = HashMap::new();
+        let mut ends = vec![];
+        let mut node = nodes.get(start).unwrap_or_else(|| panic!("No node called {start}"));
+        let mut steps = 0;
+
+        loop {
+            let position = steps as usize % directions.len();
+            if let Some(first) = seen.insert((node.name.as_str(), position), steps) {
+                let (lead_in_ends, offsets) = ends.iter().partition(|end| **end < first);
+                return Cycle {
+                    start: String::from(start), lead_in: first, length: steps - first, lead_in_ends, offsets
+                };
+            }
+            if node.name.ends_with(end_pattern) {
+                ends.push(steps);
+            }
+            let next = match directions[position] {
+                'L' => &node.left,
+                'R' => &node.right,
+                _ => panic!("Bad instruction"),
+            };
+            node = nodes.get(next.as_str()).unwrap_or_else(|| panic!("No node called {next}"));
+            steps += 1;
+        }
+    }
+
+    // whether the walk is on an end node after this many steps
+    pub fn ends_at(&self, step: u64) -> bool {
+        self.lead_in_ends.contains(&step)
+            || self.offsets.iter().any(|offset| step >= *offset && (step - offset).is_multiple_of(self.length))
+    }
+
+    // What the LCM shortcut takes for granted: the only end node on the loop
+    // is reached exactly one cycle length in, and never before that.
+    pub fn fits_shortcut(&self) -> bool {
+        self.lead_in_ends.is_empty() && self.offsets == vec![self.length]
+    }
+}
+
+// The first step where every walk is on an end node at once, if there is one.
+//
+// Ends in a lead-in happen only once, so they're checked directly. Otherwise each walk is on an
+// end node at `offset + k * length`, and every way of picking one offset per walk is a set of
+// congruences for the CRT, keeping the earliest solution that's past all the picked offsets.
+pub fn first_meeting(cycles: &[Cycle]) -> Option<u64> {
+    let one_offs = cycles.iter()
+        .flat_map(|cycle| cycle.lead_in_ends.iter())
+        .filter(|step| cycles.iter().all(|cycle| cycle.ends_at(**step)))
+        .min()
+        .cloned();
+
+    let mut combinations: Vec<(u64, u64, u64)> = vec![(0, 1, 0)];
+    for cycle in cycles {
+        combinations = combinations.iter()
+            .flat_map(|(residue, modulus, latest)| cycle.offsets.iter().filter_map(move |offset| {
+                crt(*residue, *modulus, offset % cycle.length, cycle.length)
+                    .map(|(residue, modulus)| (residue, modulus, (*latest).max(*offset)))
+            }))
+            .collect();
+    }
+    let periodic = combinations.iter()
+        .map(|(residue, modulus, latest)| {
+            if residue >= latest {
+                *residue
+            } else {
+                (latest - residue).div_ceil(*modulus).checked_mul(*modulus)
+                    .and_then(|steps| steps.checked_add(*residue))
+                    .unwrap_or_else(|| panic!("The ghosts only meet past the biggest u64"))
+            }
+        })
+        .min();
+
+    one_offs.into_iter().chain(periodic).min()
+}
+
+// x ≡ a1 (mod m1) and x ≡ a2 (mod m2) as one x ≡ a (mod lcm(m1, m2)), if they can both hold.
+// Panics if lcm(m1, m2) is too big for a u64, rather than quietly giving a wrong answer.
+fn crt(a1: u64, m1: u64, a2: u64, m2: u64) -> Option<(u64, u64)> {
+    let (g, p, _) = extended_gcd(m1 as i128, m2 as i128);
+    let diff = a2 as i128 - a1 as i128;
+    if diff % g != 0 {
+        return None;
+    }
+    let step = m2 as i128 / g;
+    // both factors are below step, which fits in a u64, so their product fits in a u128
+    let k = ((diff / g).rem_euclid(step) as u128 * p.rem_euclid(step) as u128 % step as u128) as i128;
+    let modulus = u64::try_from(m1 as i128 * step)
+        .unwrap_or_else(|_| panic!("The cycles of length {m1} and {m2} only line up past the biggest u64"));
+    let residue = (a1 as i128 + m1 as i128 * k).rem_euclid(modulus as i128);
+    Some((residue as u64, modulus))
+}
+
+// (gcd, x, y) with a * x + b * y = gcd
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nodes(lines: &[&str]) -> HashMap<String, Node> {
+        lines.iter().map(|line| Node::from_line(line)).map(|n| (n.name.clone(), n)).collect()
+    }
+
+    // the puzzle's part 2 example
+    fn example() -> HashMap<String, Node> {
+        nodes(&["11A = (11B, XXX)", "11B = (XXX, 11Z)", "11Z = (11B, XXX)", "22A = (22B, XXX)",
+                "22B = (22C, 22C)", "22C = (22Z, 22Z)", "22Z = (22B, 22B)", "XXX = (XXX, XXX)"])
+    }
+
+    #[test]
+    fn example_cycles() {
+        let nodes = example();
+        let first = Cycle::find("LR", &nodes, "11A", "Z");
+        assert_eq!(first, Cycle {
+            start: String::from("11A"), lead_in: 1, length: 2, lead_in_ends: vec![], offsets: vec![2]
+        });
+        assert!(first.fits_shortcut());
+        let second = Cycle::find("LR", &nodes, "22A", "Z");
+        assert_eq!((second.lead_in, second.length, second.offsets.clone()), (1, 6, vec![3, 6]));
+        assert!(!second.fits_shortcut());
+
+        assert_eq!(first_meeting(&[first, second]), Some(6));
+    }
+
+    #[test]
+    fn offsets_the_lcm_misses() {
+        // from A1 there's a Z after 2 steps and then every 3, from BBA after 3 and then every 2:
+        // the LCM of the first distances says 6, but they first meet at 5
+        let nodes = nodes(&["AAA = (A1, A1)", "A1 = (A2, A2)", "A2 = (AZ, AZ)", "AZ = (A1, A1)",
+                            "BBA = (B1, B1)", "B1 = (B2, B2)", "B2 = (BZ, BZ)", "BZ = (B3, B3)", "B3 = (BZ, BZ)"]);
+        let a = Cycle::find("L", &nodes, "A1", "Z");
+        let b = Cycle::find("L", &nodes, "BBA", "Z");
+        assert_eq!((a.lead_in, a.length, a.offsets.clone()), (0, 3, vec![2]));
+        assert_eq!((b.lead_in, b.length, b.offsets.clone()), (3, 2, vec![3]));
+        assert_eq!(first_meeting(&[a, b]), Some(5));
+
+        // Zs at 1, 4, 7, ... and 3, 6, 9, ... never line up
+        let c = Cycle::find("L", &nodes, "A2", "Z");
+        let d = Cycle::find("L", &nodes, "AAA", "Z");
+        assert_eq!(first_meeting(&[c, d]), None);
+    }
+
+    #[test]
+    fn ends_before_the_loop() {
+        // the only Z is on the way in, so the walks can only meet there
+        let nodes = nodes(&["AZ = (B, B)", "B = (C, C)", "C = (B, B)"]);
+        let cycle = Cycle::find("L", &nodes, "AZ", "Z");
+        assert_eq!((cycle.lead_in_ends.clone(), cycle.lead_in, cycle.length), (vec![0], 1, 2));
+        assert_eq!(first_meeting(&[cycle]), Some(0));
+    }
+
+    #[test]
+    fn chinese_remainders() {
+        assert_eq!(crt(2, 3, 3, 5), Some((8, 15)));
+        assert_eq!(crt(1, 4, 3, 6), Some((9, 12)));
+        assert_eq!(crt(0, 4, 1, 6), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "past the biggest u64")]
+    fn too_far_apart() {
+        // two big coprime cycle lengths whose product doesn't fit in a u64
+        crt(0, (1 << 40) + 1, 0, (1 << 40) - 1);
+    }
+}
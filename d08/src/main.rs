@@ -3,6 +3,11 @@ use std::fs;
 use itertools::Itertools;
 use regex::Regex;
 use lazy_static::lazy_static;
+use libaoc::config::Config;
+use libaoc::{debug, warn};
+use crate::cycles::{Cycle, first_meeting};
+
+mod cycles;
 
 lazy_static! {
     pub static ref LINE_REGEX: Regex = Regex::new(r"(\w+) = \((\w+), (\w+)\)").unwrap();
@@ -26,6 +31,10 @@ impl Node {
 }
 
 fn main() {
+    let config = Config::load();
+    if let Some("cycles") = config.positional().first().map(String::as_str) {
+        return cycles();
+    }
     part1();
     part2();
 }
@@ -64,13 +73,57 @@ fn part2() {
         .map(|n| n.name.as_str())
         .map(|start_name| find_distance(&instructions, &nodes, start_name, "Z"))
         .collect_vec();
-    println!("Part 2 Counts: {:?}", step_counts);
+    debug!("Part 2 Counts: {:?}", step_counts);
 
     let lcm = step_counts.iter()
         .map(|i| i.clone())
         .reduce(|a, b| lcm(a, b))
         .unwrap();
-    println!("Part 2: {lcm}");
+
+    // the LCM only works when every start loops back to its Z right after reaching it,
+    // so check it against the answer from the full cycles
+    let cycles = find_cycles(&instructions, &nodes);
+    for cycle in cycles.iter().filter(|cycle| !cycle.fits_shortcut()) {
+        warn!("{} doesn't fit the LCM shortcut: {}", cycle.start, describe(cycle));
+    }
+    match first_meeting(&cycles) {
+        Some(steps) if steps == lcm => println!("Part 2: {lcm}"),
+        Some(steps) => {
+            warn!("The LCM of the first distances ({lcm}) is wrong, the ghosts first meet at {steps}");
+            println!("Part 2: {steps}");
+        }
+        None => {
+            warn!("The LCM of the first distances ({lcm}) is wrong, the ghosts never all stand on Z at once");
+            println!("Part 2: none");
+        }
+    }
+}
+
+fn find_cycles(instructions: &str, nodes: &HashMap<String, Node>) -> Vec<Cycle> {
+    nodes.keys()
+        .filter(|name| name.ends_with("A"))
+        .sorted()
+        .map(|start| Cycle::find(instructions, nodes, start, "Z"))
+        .collect()
+}
+
+fn describe(cycle: &Cycle) -> String {
+    format!("lead-in {}, cycle length {}, Z at {:?}{}",
+            cycle.lead_in, cycle.length, cycle.offsets,
+            if cycle.lead_in_ends.is_empty() { String::new() } else { format!(" and once at {:?}", cycle.lead_in_ends) })
+}
+
+// `d08 cycles` shows where each start's walk loops, and when it's on a Z
+fn cycles() {
+    let (instructions, nodes) = read_file("input");
+    let cycles = find_cycles(&instructions, &nodes);
+    for cycle in &cycles {
+        println!("{}: {}", cycle.start, describe(cycle));
+    }
+    match first_meeting(&cycles) {
+        Some(steps) => println!("All on Z at once after {steps} steps"),
+        None => println!("Never all on Z at once"),
+    }
 }
 
 fn read_file(filename: &str) -> (String, HashMap<String, Node>) {
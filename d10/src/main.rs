@@ -1,6 +1,8 @@
 use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
 use std::fs;
+use libaoc::animate::{self, Animation, Frame};
+use libaoc::config::Config;
 use crate::Color::{BLUE, RED};
 use crate::Direction::{LEFT, RIGHT, UP, DOWN};
 
@@ -222,6 +224,8 @@ enum Color {
 }
 
 fn main() {
+    let config = Config::load();
+    let mut animation = Animation::from_config(&config);
     let map = read_input("input");
 
     let start_location = map.values()
@@ -246,7 +250,9 @@ fn main() {
             }
         }
         has_explored.push(cur_node);
+        animation.frame(|| draw_map(&map, cur_node));
     }
+    animation.finish();
 
     let furthest_distance = has_explored.iter()
         .map(|n| n.distance.borrow().unwrap())
@@ -296,6 +302,29 @@ fn main() {
         .get(&UP)
         .unwrap();
     println!("Outside is {:?}", outside_color);
+
+    // spelled out so the runner can check it
+    let inside_count = if outside_color == RED { blue_count } else { red_count };
+    println!("Part 2: {inside_count}");
+}
+
+// pipes in grey, the part of the loop we've walked in yellow, and where we are now in red
+fn draw_map(map: &HashMap<Point, Node>, cur_node: &Node) -> Frame {
+    let width = map.keys().map(|p| p.0).max().unwrap() + 1;
+    let height = map.keys().map(|p| p.1).max().unwrap() + 1;
+    let mut frame = Frame::new(width as usize, height as usize, animate::BLACK);
+    for node in map.values() {
+        let color = if node.distance.borrow().is_some() {
+            animate::YELLOW
+        } else if node.val != '.' {
+            animate::GREY
+        } else {
+            continue
+        };
+        frame.set(node.location.0 as i64, node.location.1 as i64, color);
+    }
+    frame.set(cur_node.location.0 as i64, cur_node.location.1 as i64, animate::RED);
+    frame
 }
 
 fn read_input(filename: &str) -> HashMap<Point, Node> {
@@ -1,6 +1,8 @@
 use std::cmp::{max, min};
 use std::collections::{HashSet};
 use std::fs;
+use libaoc::config::Config;
+use libaoc::trace;
 
 type Point = (usize, usize);
 
@@ -56,22 +58,23 @@ impl Universe {
 }
 
 fn main() {
-    part1();
-    part2();
+    let config = Config::load();
+    part1(&config);
+    part2(&config);
 }
 
-fn part1() {
+fn part1(config: &Config) {
     let galaxies = read_file("input");
-    let universe = Universe::from(&galaxies, 2);
+    let universe = Universe::from(&galaxies, config.get("part1_expansion", 2));
 
     let distance_sum = sum_distances(&galaxies, &universe);
 
     println!("Part 1: {distance_sum}");
 }
 
-fn part2() {
+fn part2(config: &Config) {
     let galaxies = read_file("input");
-    let universe = Universe::from(&galaxies, 1_000_000);
+    let universe = Universe::from(&galaxies, config.get("part2_expansion", 1_000_000));
 
     let distance_sum = sum_distances(&galaxies, &universe);
 
@@ -87,7 +90,7 @@ fn sum_distances(galaxies: &HashSet<Galaxy>, universe: &Universe) -> usize {
         })
         .map(|(ga, gb)| {
             let distance = ga.distance_to(gb, &universe);
-            // println!("{} -> {}: {distance}", ga.name, gb.name);
+            trace!("{} -> {}: {distance}", ga.name, gb.name);
             distance
         })
         .sum();
@@ -1,6 +1,8 @@
 use std::fs;
 use std::time::SystemTime;
 use rayon::prelude::*;
+use libaoc::config::Config;
+use libaoc::trace;
 
 struct Puzzle {
     damaged_counts: Vec<usize>,
@@ -21,10 +23,10 @@ impl Puzzle {
         }
     }
 
-    fn from_line_but_worse(line: &str) -> Puzzle {
+    fn from_line_but_worse(line: &str, unfold: usize) -> Puzzle {
         let parts: Vec<&str> = line.split(" ").collect();
         let mut corrupted_data = String::from(parts[0]);
-        for _ in 0..4 {
+        for _ in 1..unfold {
             corrupted_data.push('?');
             corrupted_data.push_str(parts[0]);
         }
@@ -34,7 +36,7 @@ impl Puzzle {
             .map(|num| num.parse().unwrap())
             .collect();
         let mut damaged_groups: Vec<usize> = vec![];
-        for _ in 0..5 {
+        for _ in 0..unfold {
             base_groups.iter().for_each(|n| damaged_groups.push(*n));
         }
         Puzzle {
@@ -138,15 +140,16 @@ impl Puzzle {
 }
 
 fn main() {
+    let config = Config::load();
     part1();
-    part2();
+    part2(&config);
 }
 
 fn part1() {
-    let possibilities: usize = read_puzzles("input", false).iter()
+    let possibilities: usize = read_puzzles("input", None).iter()
         .map(|p| {
             let combos = p.possible_combos();
-            println!("{combos}");
+            trace!("{combos}");
             combos
         })
         .sum();
@@ -154,15 +157,15 @@ fn part1() {
     println!("Part 1: {possibilities}");
 }
 
-fn part2() {
+fn part2(config: &Config) {
     let begin = SystemTime::now();
-    let possibilities: usize = read_puzzles("input", true)
+    let possibilities: usize = read_puzzles("input", Some(config.get("unfold", 5)))
         .iter()
         .enumerate()
         .par_bridge()
         .map(|(id, p)| {
             let combos = p.possible_combos();
-            println!("{id}: {combos}");
+            trace!("{id}: {combos}");
             combos
         })
         .sum();
@@ -172,13 +175,13 @@ fn part2() {
              end.duration_since(begin).unwrap().as_secs_f32());
 }
 
-fn read_puzzles(filename: &str, funky_mode: bool) -> Vec<Puzzle> {
+// when unfolding, each line is repeated that many times
+fn read_puzzles(filename: &str, unfold: Option<usize>) -> Vec<Puzzle> {
     fs::read_to_string(filename).unwrap()
         .lines()
-        .map(if funky_mode {
-            Puzzle::from_line_but_worse
-        } else {
-            Puzzle::from_line
+        .map(|line| match unfold {
+            Some(times) => Puzzle::from_line_but_worse(line, times),
+            None => Puzzle::from_line(line)
         })
         .collect()
 }
@@ -5,6 +5,9 @@ use std::hash::{Hash, Hasher};
 use std::num::Wrapping;
 use std::rc::Rc;
 use itertools::Itertools;
+use libaoc::animate::{Animation, Frame, BLACK, GREY, YELLOW};
+use libaoc::config::Config;
+use libaoc::debug;
 
 type Coord = i32;
 type Point = (Coord, Coord);
@@ -159,8 +162,15 @@ impl Board {
         self.clone_with(new_rocks)
     }
 
-    fn cycle(&self) -> Board {
-        self.roll_n().roll_w().roll_s().roll_e()
+    fn cycle(&self, animation: &mut Animation) -> Board {
+        // keep a frame after each tilt, so you can watch the rocks slide around
+        let mut board = self.roll_n();
+        animation.frame(|| board.to_frame());
+        for roll in [Board::roll_w, Board::roll_s, Board::roll_e] {
+            board = roll(&board);
+            animation.frame(|| board.to_frame());
+        }
+        board
     }
 
     fn score(&self) -> Coord {
@@ -186,6 +196,13 @@ impl Board {
 
         string
     }
+
+    fn to_frame(&self) -> Frame {
+        let mut frame = Frame::new(self.width as usize, self.height as usize, BLACK);
+        self.blocks.iter().for_each(|(x, y)| frame.set(*x as i64, *y as i64, GREY));
+        self.rocks.iter().for_each(|(x, y)| frame.set(*x as i64, *y as i64, YELLOW));
+        frame
+    }
 }
 
 impl PartialEq<Self> for Board {
@@ -212,26 +229,35 @@ impl Hash for Board {
 }
 
 fn main() {
+    let config = Config::load();
     part1();
-    part2();
+    part2(&config);
 }
 
 fn part1() {
     let board = load_board("input");
     let new_board = board.roll_n();
-    // println!("{}", board.to_string());
+    debug!("before rolling north:\n{}", board.to_string());
     println!("Part 1: {}", new_board.score());
 }
 
-fn part2() {
+fn part2(config: &Config) {
+    let total_cycles: usize = config.get("cycles", 1_000_000_000);
+    let mut animation = Animation::from_config(config);
     let board = load_board("input");
+    animation.frame(|| board.to_frame());
     let mut cur_board = Rc::new(board);
     let mut count = 0;
     let mut boards_by_count: Vec<Rc<Board>> = vec![Rc::clone(&cur_board)];
     let mut count_map: HashMap<Rc<Board>, usize> = HashMap::from([(Rc::clone(&cur_board), 0)]);
     loop {
+        // if we're asked for fewer cycles than it takes to find a loop, just run them all
+        if count == total_cycles {
+            println!("Part 2: {}", cur_board.score());
+            break
+        }
         count += 1;
-        cur_board = Rc::new(cur_board.cycle());
+        cur_board = Rc::new(cur_board.cycle(&mut animation));
         if count_map.contains_key(&cur_board) {
             // A B C D E F C D E F C  D  E  F  C  D  E  F
             // 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17
@@ -240,7 +266,7 @@ fn part2() {
             // 17 - 2 = 15, 15 % 4 = 3, 17 ~= 2 + 3 = 5 (F)
             let cycle_start = count_map.get(&cur_board).unwrap();
             let cycle_length = count - cycle_start;
-            let remaining_length = 1_000_000_000 - cycle_start;
+            let remaining_length = total_cycles - cycle_start;
             let equivalent_length = remaining_length % cycle_length;
             let equivalent_board = &boards_by_count[cycle_start + equivalent_length];
 
@@ -250,6 +276,7 @@ fn part2() {
         boards_by_count.push(Rc::clone(&cur_board));
         count_map.insert(Rc::clone(&cur_board), count);
     }
+    animation.finish();
 
 }
 
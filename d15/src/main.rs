@@ -1,4 +1,5 @@
 use std::fs;
+use libaoc::{debug, trace};
 use crate::Instruction::{Add, Remove};
 
 struct Hash {
@@ -74,7 +75,7 @@ fn part1() {
     let sum: u32 = read_steps("input").iter()
         .map(|word| {
             let h = Hash::of_word(word);
-            // println!("{}: {}", word, h.val);
+            trace!("{}: {}", word, h.val);
             h.val
         })
         .sum();
@@ -121,7 +122,7 @@ fn part2() {
     let sum: usize = bins.iter().enumerate()
         .flat_map(|(id, bin)| {
             if !bin.is_empty() {
-                // println!("Bin {id}: {:?}", bin);
+                debug!("Bin {id}: {:?}", bin);
             }
             bin.iter().enumerate()
                 .map(move |(slot, lens)| {
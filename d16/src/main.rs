@@ -1,5 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::fs;
+use libaoc::animate::{Animation, Frame, BLACK, GREY, RED, YELLOW};
+use libaoc::config::Config;
 use crate::Tile::{*};
 use crate::Direction::{*};
 
@@ -9,14 +11,15 @@ struct Game {
 }
 
 impl Game {
-    fn run(&self) -> usize {
-        self.run_starting_at((-1, 0), Right)
+    fn run(&self, animation: &mut Animation) -> usize {
+        self.run_starting_at((-1, 0), Right, animation)
     }
 
-    fn run_starting_at(&self, start_point: Point, start_direction: Direction) -> usize {
+    fn run_starting_at(&self, start_point: Point, start_direction: Direction,
+                       animation: &mut Animation) -> usize {
         // get the unique (Point, Direction) pairs
         let mut visited = HashSet::new();
-        self.run_from(start_point, start_direction, &mut visited);
+        self.run_from(start_point, start_direction, &mut visited, animation);
 
         // filter down to just the unique points
         let squares: HashSet<Point> = visited.iter()
@@ -30,7 +33,8 @@ impl Game {
     // visited is (entered-point, entered-direction)
     fn run_from(&self, start_point: Point,
                 start_direction: Direction,
-                visited: &mut HashSet<(Point, Direction)>) {
+                visited: &mut HashSet<(Point, Direction)>,
+                animation: &mut Animation) {
         let mut cur_point = start_point;
         let mut cur_direction = start_direction;
         loop {
@@ -47,6 +51,7 @@ impl Game {
             if !new_visit {
                 break
             }
+            animation.frame(|| self.to_frame(visited, cur_point));
 
             // figure out where we're going next
             let (new_direction, new_split) = Self::new_direction(
@@ -55,12 +60,26 @@ impl Game {
 
             // if we've been split, run out the clock on that, then move in the other direction
             if let Some(split_dir) = new_split {
-                self.run_from(cur_point, split_dir, visited);
+                self.run_from(cur_point, split_dir, visited, animation);
             }
             cur_direction = new_direction;
         }
     }
 
+    // energized tiles in yellow, the head of the beam we're following in red
+    fn to_frame(&self, visited: &HashSet<(Point, Direction)>, beam: Point) -> Frame {
+        let width = self.board.keys().map(|(x, _)| *x).max().unwrap() + 1;
+        let height = self.board.keys().map(|(_, y)| *y).max().unwrap() + 1;
+        let mut frame = Frame::new(width as usize, height as usize, BLACK);
+        self.board.iter()
+            .filter(|(_, tile)| **tile != Blank)
+            .for_each(|((x, y), _)| frame.set(*x as i64, *y as i64, GREY));
+        visited.iter()
+            .for_each(|((x, y), _)| frame.set(*x as i64, *y as i64, YELLOW));
+        frame.set(beam.0 as i64, beam.1 as i64, RED);
+        frame
+    }
+
     fn direction_deltas(direction: Direction) -> Point {
         match direction {
             Up => (0, -1),
@@ -127,13 +146,16 @@ enum Direction {
 }
 
 fn main() {
-    part1();
+    let config = Config::load();
+    part1(&config);
     part2();
 }
 
-fn part1() {
+fn part1(config: &Config) {
     let board = load_board("input");
-    let visited = board.run();
+    let mut animation = Animation::from_config(config);
+    let visited = board.run(&mut animation);
+    animation.finish();
     println!("Part 1: {}", visited);
 }
 
@@ -165,7 +187,7 @@ fn part2() {
     }
 
     let max_coverage = entries.iter()
-        .map(|(point, dir)| game.run_starting_at(*point, *dir))
+        .map(|(point, dir)| game.run_starting_at(*point, *dir, &mut Animation::disabled()))
         .max()
         .unwrap();
 
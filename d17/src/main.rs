@@ -4,6 +4,8 @@ use std::fs;
 use std::hash::{Hash};
 use lib2d::{corners, dir_delta, dir_opposite, Direction, Point2d};
 use lib2d::Direction::{*};
+use libaoc::animate::{Animation, Frame, BLACK, BLUE, RED};
+use libaoc::config::Config;
 
 // TODO: Djikstra's, but for each node, keep track of lowest score for
 //       each of 0, 1, 2, 3 straight moves to get there?
@@ -112,7 +114,7 @@ impl PartialOrd for CostedTile {
 }
 
 impl Game {
-    fn find_path(&self) -> i32 {
+    fn find_path(&self, animation: &mut Animation) -> i32 {
         let (_, target_point) = corners(self.map.keys()).unwrap();
         let mut explored: HashSet<Tile> = HashSet::new();
         let starting_tile = CostedTile::new(Point2d::new(0, 0), Right, 0, 0);
@@ -127,6 +129,7 @@ impl Game {
             for tile in cur_pos.tile.self_and_worse(self) {
                 explored.insert(tile);
             }
+            animation.frame(|| self.to_frame(&explored, cur_pos.tile.point));
             [Up, Down, Left, Right].into_iter()
                 .flat_map(|dir| cur_pos.try_travel(dir, self).into_iter())
                 .filter(|ct| !explored.contains(&ct.tile))
@@ -134,22 +137,44 @@ impl Game {
         }
         panic!("Never found my way to El Dorado");
     }
+
+    // heat loss as shades of grey, explored squares in blue, and where we are right now in red
+    fn to_frame(&self, explored: &HashSet<Tile>, current: Point2d<i32>) -> Frame {
+        let (_, bottom_right) = corners(self.map.keys()).unwrap();
+        let mut frame = Frame::new(bottom_right.x as usize + 1, bottom_right.y as usize + 1, BLACK);
+        for (point, heat) in self.map.iter() {
+            let shade = (heat * 25) as u8;
+            frame.set(point.x as i64, point.y as i64, (shade, shade, shade));
+        }
+        for tile in explored.iter() {
+            frame.set(tile.point.x as i64, tile.point.y as i64, BLUE);
+        }
+        frame.set(current.x as i64, current.y as i64, RED);
+        frame
+    }
 }
 
 fn main() {
-    part1();
-    part2();
+    let config = Config::load();
+    part1(&config);
+    part2(&config);
 }
 
-fn part1() {
-    let game = load_map("input", 1, 3);
-    let score = game.find_path();
+fn part1(config: &Config) {
+    let game = load_map("input",
+                        config.get("part1_min_movement", 1),
+                        config.get("part1_max_streak", 3));
+    let mut animation = Animation::from_config(config);
+    let score = game.find_path(&mut animation);
+    animation.finish();
     println!("Part 1: {score}");
 }
 
-fn part2() {
-    let game = load_map("input", 4, 10);
-    let score = game.find_path();
+fn part2(config: &Config) {
+    let game = load_map("input",
+                        config.get("part2_min_movement", 4),
+                        config.get("part2_max_streak", 10));
+    let score = game.find_path(&mut Animation::disabled());
     println!("Part 2: {score}");
 }
 
@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 use std::fs;
 use lib2d::{corners, Point2d};
+use libaoc::debug;
 use crate::Direction::*;
 
 struct Instruction {
@@ -32,14 +33,22 @@ fn main() {
 fn part1() {
     let instructions = read_instructions("input");
     let dug = dig(&instructions);
-    // print_pool(&dug);
+    debug!("dug out:\n{}", draw_pool(&dug));
     let volume = count_inside_points(&dug);
     println!("Part 1: {volume}");
 }
 
 fn part2() {
     let instructions = read_instructions("input");
-    let vertices = trace(&instructions);
+    let total_area = shoelace_volume(&instructions);
+    println!("Part 2: {total_area}");
+    // Should: 952408144115
+    //    Got: 952408144114
+}
+
+// compute the dug-out volume from the trench's corners, without visiting every square
+fn shoelace_volume(instructions: &Vec<Instruction>) -> i64 {
+    let vertices = trace(instructions);
     // this algorithm stolen from: https://www.mathopenref.com/coordpolygonarea.html
     let area = vertices.iter().zip(vertices.iter().skip(1))
         .map(|(pa, pb)| {
@@ -66,10 +75,7 @@ fn part2() {
         DDC
     */
 
-    let total_area = area + outline / 2 + 1;
-    println!("Part 2: {total_area}");
-    // Should: 952408144115
-    //    Got: 952408144114
+    area + outline / 2 + 1
 }
 
 // instead of collecting all of the points, collect all of the lines that make up this shape
@@ -101,18 +107,20 @@ fn dig(instructions: &Vec<Instruction>) -> HashSet<Point2d<i64>> {
 }
 
 // draw the trench from it's points
-fn print_pool(points: &HashSet<Point2d<i64>>) {
+fn draw_pool(points: &HashSet<Point2d<i64>>) -> String {
     let (min, max) = corners(points.iter()).unwrap();
+    let mut drawing = String::new();
     for y in min.y..=max.y {
         for x in min.x..=max.x {
             if points.contains(&Point2d::new(x, y)) {
-                print!("#");
+                drawing.push('#');
             } else {
-                print!(".");
+                drawing.push('.');
             }
         }
-        print!("\n");
+        drawing.push('\n');
     }
+    drawing
 }
 
 // count the inside points using a "paint" method
@@ -166,3 +174,70 @@ fn read_instructions(filename: &str) -> Vec<Instruction> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use libaoc::difftest::{Differential, shrink_int, shrink_vec};
+    use crate::{count_inside_points, dig, shoelace_volume, Direction, Instruction};
+    use crate::Direction::*;
+
+    // (width, depth) of each column of a trench that's flat along the top and
+    // ragged along the bottom, so the origin is always the top-left corner
+    // and (1, 1) is always inside, like count_inside_points expects
+    type Skyline = Vec<(i64, i64)>;
+
+    fn valid_skyline(columns: &Skyline) -> bool {
+        !columns.is_empty() &&
+            columns.iter().map(|(width, _)| width).sum::<i64>() >= 2 &&
+            columns.iter().all(|(width, depth)| *width >= 1 && *depth >= 2)
+    }
+
+    fn skyline_instructions(columns: &Skyline) -> Vec<Instruction> {
+        let width: i64 = columns.iter().map(|(w, _)| w).sum();
+        let mut moves: Vec<(Direction, i64)> = vec![(Right, width)];
+        let mut cur_depth = 0;
+        for (column_width, column_depth) in columns.iter().rev() {
+            if *column_depth > cur_depth {
+                moves.push((Down, column_depth - cur_depth));
+            } else if *column_depth < cur_depth {
+                moves.push((Up, cur_depth - column_depth));
+            }
+            cur_depth = *column_depth;
+            moves.push((Left, *column_width));
+        }
+        moves.push((Up, cur_depth));
+
+        moves.into_iter()
+            .map(|(direction, length)| Instruction {
+                direction, length, hex_direction: direction, hex_length: length
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shoelace_matches_flood_fill() {
+        Differential::new(|rng| {
+            // the first column is at least 2 wide, so the whole trench is too
+            let mut skyline: Skyline = vec![(rng.range(2..6), rng.range(2..8))];
+            for _ in 0..rng.range(0..5) {
+                skyline.push((rng.range(1..6), rng.range(2..8)));
+            }
+            skyline
+        })
+            .shrink_with(|skyline| {
+                shrink_vec(skyline, |(width, depth)| {
+                    let mut candidates: Vec<(i64, i64)> = shrink_int(*width).into_iter()
+                        .map(|w| (w, *depth))
+                        .collect();
+                    candidates.extend(shrink_int(*depth).into_iter().map(|d| (*width, d)));
+                    candidates
+                })
+                    .into_iter()
+                    .filter(valid_skyline)
+                    .collect()
+            })
+            .assert_agree(
+                |skyline| count_inside_points(&dig(&skyline_instructions(skyline))) as i64,
+                |skyline| shoelace_volume(&skyline_instructions(skyline)));
+    }
+}
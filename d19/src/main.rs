@@ -1,8 +1,10 @@
 use std::cmp::{max, min};
 use std::collections::{HashMap};
 use std::fs;
+use std::ops::Range;
 use regex::Regex;
 use lazy_static::lazy_static;
+use libaoc::config::Config;
 use crate::TestType::{*};
 use crate::Determination::{*};
 
@@ -145,8 +147,9 @@ fn invert_test(test: &TestType) -> TestType {
 }
 
 fn main() {
+    let config = Config::load();
     part1();
-    part2();
+    part2(&config);
 }
 
 fn part1() {
@@ -157,23 +160,24 @@ fn part1() {
     println!("Part 1: {score}");
 }
 
-fn part2() {
+fn part2(config: &Config) {
     let game = parse_game("input");
+    let ratings = config.get("min_rating", 1)..(config.get("max_rating", 4000) + 1);
     let rule_flows = game.discover_rule_flows();
     let count: usize = rule_flows.iter()
         .filter(|(_, result)| *result == true)
         .map(|(rf, _)| rf)
-        .map(size_ruleflow)
+        .map(|rf| size_ruleflow(rf, &ratings))
         .sum();
     println!("Part 2: {count}");
 }
 
-fn size_ruleflow(rules: &Vec<TestType>) -> usize {
+fn size_ruleflow(rules: &[TestType], ratings: &Range<u32>) -> usize {
     let mut ranges = HashMap::from([
-        ('x', 1..4001),
-        ('m', 1..4001),
-        ('a', 1..4001),
-        ('s', 1..4001),
+        ('x', ratings.clone()),
+        ('m', ratings.clone()),
+        ('a', ratings.clone()),
+        ('s', ratings.clone()),
     ]);
     for rule in rules.iter() {
            match rule {
@@ -259,9 +263,9 @@ fn parse_part(part_str: &str) -> Part {
 
 #[cfg(test)]
 mod tests {
-    use itertools::Either::{Left, Right};
     use crate::{parse_part, parse_rule, RULE, WORKFLOW};
     use crate::TestType::{*};
+    use crate::Determination::{*};
 
     #[test]
     fn regex_test() {
@@ -290,15 +294,15 @@ mod tests {
     fn parse_rule_test() {
         let rule1 = parse_rule("a<2006:qkq");
         assert_eq!(rule1.test, LT('a', 2006));
-        assert_eq!(rule1.determination, Left(String::from("qkq")));
+        assert_eq!(rule1.determination, WorkflowLink(String::from("qkq")));
 
         let rule2 = parse_rule("m>2090:A");
         assert_eq!(rule2.test, GT('m', 2090));
-        assert_eq!(rule2.determination, Right(true));
+        assert_eq!(rule2.determination, FinalResult(true));
 
         let rule3 = parse_rule("rfg");
         assert_eq!(rule3.test, ALWAYS);
-        assert_eq!(rule3.determination, Left(String::from("rfg")));
+        assert_eq!(rule3.determination, WorkflowLink(String::from("rfg")));
     }
 
     #[test]
@@ -0,0 +1,244 @@
+use std::fs;
+use std::io::{stderr, BufWriter, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::config::Config;
+use crate::debug;
+
+pub type Rgb = (u8, u8, u8);
+
+pub const BLACK: Rgb = (0, 0, 0);
+pub const WHITE: Rgb = (255, 255, 255);
+pub const GREY: Rgb = (90, 90, 90);
+pub const RED: Rgb = (220, 50, 47);
+pub const YELLOW: Rgb = (250, 200, 40);
+pub const BLUE: Rgb = (38, 139, 210);
+pub const GREEN: Rgb = (90, 180, 60);
+
+// one picture of a grid-shaped simulation, one color per cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    cells: Vec<Rgb>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Frame {
+        Frame { width, height, cells: vec![background; width * height] }
+    }
+
+    // points off the edge of the frame are ignored, so callers don't have to bounds-check
+    pub fn set(&mut self, x: i64, y: i64, color: Rgb) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.cells[y as usize * self.width + x as usize] = color;
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.cells[y * self.width + x]
+    }
+
+    // binary PPM, each cell blown up to a scale-by-scale square
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width * scale, self.height * scale).into_bytes();
+        for y in 0..self.height {
+            let row: Vec<u8> = (0..self.width)
+                .flat_map(|x| {
+                    let (r, g, b) = self.get(x, y);
+                    [r, g, b].repeat(scale)
+                })
+                .collect();
+            for _ in 0..scale {
+                ppm.extend_from_slice(&row);
+            }
+        }
+        ppm
+    }
+
+    // two rows per line of text, using the upper-half-block's foreground and background colors
+    pub fn to_ansi(&self) -> String {
+        let mut text = String::new();
+        for y in (0..self.height).step_by(2) {
+            for x in 0..self.width {
+                let (tr, tg, tb) = self.get(x, y);
+                let (br, bg, bb) = if y + 1 < self.height { self.get(x, y + 1) } else { BLACK };
+                text.push_str(&format!("\x1b[38;2;{tr};{tg};{tb}m\x1b[48;2;{br};{bg};{bb}m▀"));
+            }
+            text.push_str("\x1b[0m\n");
+        }
+        text
+    }
+}
+
+pub trait FrameSink {
+    fn capture(&mut self, frame: &Frame);
+    fn finish(&mut self) {}
+}
+
+// frames/frame_00000.ppm, frames/frame_00001.ppm, ...
+pub struct PpmSequence {
+    dir: PathBuf,
+    scale: usize,
+    count: usize,
+}
+
+impl PpmSequence {
+    pub fn new(dir: PathBuf, scale: usize) -> PpmSequence {
+        fs::create_dir_all(&dir)
+            .unwrap_or_else(|e| panic!("Couldn't create frame directory {}: {e}", dir.display()));
+        PpmSequence { dir, scale, count: 0 }
+    }
+}
+
+impl FrameSink for PpmSequence {
+    fn capture(&mut self, frame: &Frame) {
+        let path = self.dir.join(format!("frame_{:05}.ppm", self.count));
+        fs::write(&path, frame.to_ppm(self.scale))
+            .unwrap_or_else(|e| panic!("Couldn't write frame {}: {e}", path.display()));
+        self.count += 1;
+    }
+
+    fn finish(&mut self) {
+        debug!("wrote {} frames to {}", self.count, self.dir.display());
+    }
+}
+
+// plays the frames on stderr as they come, no faster than fps
+pub struct TerminalReplay {
+    frame_time: Duration,
+    last_frame: Option<Instant>,
+}
+
+impl TerminalReplay {
+    pub fn new(fps: f64) -> TerminalReplay {
+        assert!(fps > 0.0, "--fps should be more than 0, not {fps}");
+        TerminalReplay { frame_time: Duration::from_secs_f64(1.0 / fps), last_frame: None }
+    }
+}
+
+impl FrameSink for TerminalReplay {
+    fn capture(&mut self, frame: &Frame) {
+        match self.last_frame {
+            // clear the screen before the first frame
+            None => eprint!("\x1b[2J"),
+            Some(last) => {
+                let elapsed = last.elapsed();
+                if elapsed < self.frame_time {
+                    thread::sleep(self.frame_time - elapsed);
+                }
+            }
+        }
+        self.last_frame = Some(Instant::now());
+        let mut out = BufWriter::new(stderr());
+        // move the cursor home and draw over the previous frame
+        let _ = write!(out, "\x1b[H{}", frame.to_ansi());
+    }
+}
+
+// The hook a simulation calls at each step.
+//
+//   --animate=ppm       write frames/frame_NNNNN.ppm (--frames_dir, --scale=4)
+//   --animate=terminal  replay in the terminal (--fps=10)
+//   --every=N           only keep every Nth step
+//
+// Without --animate, frame() never calls its drawing closure, so it costs next to nothing.
+pub struct Animation {
+    sink: Option<Box<dyn FrameSink>>,
+    every: usize,
+    step: usize,
+}
+
+impl Animation {
+    pub fn from_config(config: &Config) -> Animation {
+        let sink: Option<Box<dyn FrameSink>> = match config.get("animate", String::new()).as_str() {
+            "" => None,
+            "ppm" => Some(Box::new(PpmSequence::new(
+                PathBuf::from(config.get("frames_dir", String::from("frames"))),
+                config.get("scale", 4)))),
+            "terminal" => Some(Box::new(TerminalReplay::new(config.get("fps", 10.0)))),
+            other => panic!("Unknown animation type {other}, expected ppm or terminal"),
+        };
+        let every = config.get("every", 1);
+        assert!(every > 0, "--every should be at least 1");
+
+        Animation { sink, every, step: 0 }
+    }
+
+    pub fn disabled() -> Animation {
+        Animation { sink: None, every: 1, step: 0 }
+    }
+
+    pub fn with_sink(sink: Box<dyn FrameSink>) -> Animation {
+        Animation { sink: Some(sink), every: 1, step: 0 }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    pub fn frame(&mut self, draw: impl FnOnce() -> Frame) {
+        if let Some(sink) = self.sink.as_mut() {
+            if self.step.is_multiple_of(self.every) {
+                sink.capture(&draw());
+            }
+            self.step += 1;
+        }
+    }
+
+    pub fn finish(&mut self) {
+        if let Some(sink) = self.sink.as_mut() {
+            sink.finish();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use super::*;
+
+    struct Recorder(Rc<RefCell<Vec<Frame>>>);
+
+    impl FrameSink for Recorder {
+        fn capture(&mut self, frame: &Frame) {
+            self.0.borrow_mut().push(frame.clone());
+        }
+    }
+
+    #[test]
+    fn ppm_scaling() {
+        let mut frame = Frame::new(2, 1, BLACK);
+        frame.set(1, 0, WHITE);
+        frame.set(5, 5, RED);
+        let ppm = frame.to_ppm(2);
+        let header = b"P6\n4 2\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        let pixels = &ppm[header.len()..];
+        assert_eq!(pixels.len(), 4 * 2 * 3);
+        assert_eq!(&pixels[..12], &[0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 255, 255]);
+    }
+
+    #[test]
+    #[should_panic(expected = "--fps should be more than 0")]
+    fn zero_fps() {
+        Animation::from_config(&Config::from_parts("", &["--animate=terminal", "--fps=0"]));
+    }
+
+    #[test]
+    fn skips_frames() {
+        let frames = Rc::new(RefCell::new(vec![]));
+        let mut animation = Animation::with_sink(Box::new(Recorder(Rc::clone(&frames))));
+        animation.every = 3;
+        for step in 0..7 {
+            animation.frame(|| Frame::new(step, 1, BLACK));
+        }
+        let widths: Vec<usize> = frames.borrow().iter().map(|f| f.width).collect();
+        assert_eq!(widths, vec![0, 3, 6]);
+
+        let mut disabled = Animation::disabled();
+        disabled.frame(|| panic!("shouldn't draw when disabled"));
+    }
+}
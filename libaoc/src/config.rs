@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs;
+use std::str::FromStr;
+
+pub const DEFAULT_CONFIG_FILE: &str = "config";
+
+// Puzzle parameters, read from an optional `config` file next to the input,
+// then overridden by `--key=value` flags on the command line.
+//
+// The config file holds one `key = value` per line; blank lines and `#` comments are ignored.
+// `--config=path` reads a different file, and a bare `--flag` is the same as `--flag=true`.
+// Short flags are counted, so `-vv` is the same as `--v=2`.
+// Anything else is kept as a positional argument.
+#[derive(Debug, Default)]
+pub struct Config {
+    values: HashMap<String, String>,
+    positional: Vec<String>,
+}
+
+impl Config {
+    pub fn load() -> Config {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let (flags, positional) = parse_args(&args);
+
+        let config_file = flags.get("config")
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_CONFIG_FILE);
+        let mut values = match fs::read_to_string(config_file) {
+            Ok(contents) => parse_config(&contents),
+            // the default file is optional, but one asked for by name had better be there
+            Err(_) if !flags.contains_key("config") => HashMap::new(),
+            Err(e) => panic!("Couldn't read config file {config_file}: {e}"),
+        };
+        values.extend(flags);
+
+        Config { values, positional }
+    }
+
+    pub fn from_parts(contents: &str, args: &[&str]) -> Config {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        let (flags, positional) = parse_args(&args);
+        let mut values = parse_config(contents);
+        values.extend(flags);
+
+        Config { values, positional }
+    }
+
+    pub fn get<T>(&self, key: &str, default: T) -> T
+    where T: FromStr, T::Err: Debug {
+        match self.values.get(key) {
+            None => default,
+            Some(value) => value.parse()
+                .unwrap_or_else(|e| panic!("Couldn't parse config value {key}={value}: {:?}", e))
+        }
+    }
+
+    // a comma-separated list, like `times = 41,96,88,94`
+    pub fn get_list<T>(&self, key: &str, default: Vec<T>) -> Vec<T>
+    where T: FromStr, T::Err: Debug {
+        match self.values.get(key) {
+            None => default,
+            Some(value) => value.split(',')
+                .map(|item| item.trim().parse()
+                    .unwrap_or_else(|e| panic!("Couldn't parse config value {key}={value}: {:?}", e)))
+                .collect()
+        }
+    }
+
+    pub fn flag(&self, key: &str) -> bool {
+        self.get(key, false)
+    }
+
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+}
+
+fn parse_args(args: &[String]) -> (HashMap<String, String>, Vec<String>) {
+    let mut flags = HashMap::new();
+    let mut positional = vec![];
+    for arg in args {
+        if let Some(flag) = arg.strip_prefix("--") {
+            let (key, value) = flag.split_once('=').unwrap_or((flag, "true"));
+            flags.insert(String::from(key), String::from(value));
+        } else if is_short_flags(arg) {
+            // short flags count how many times they're given, so `-vv` is `--v=2`
+            for ch in arg.chars().skip(1) {
+                let count = flags.get(&ch.to_string())
+                    .and_then(|c| c.parse::<usize>().ok())
+                    .unwrap_or(0);
+                flags.insert(ch.to_string(), (count + 1).to_string());
+            }
+        } else {
+            positional.push(arg.clone())
+        }
+    }
+    (flags, positional)
+}
+
+// `-v` or `-vv`, but not a negative number
+fn is_short_flags(arg: &str) -> bool {
+    arg.len() > 1 && arg.starts_with('-') && arg.chars().skip(1).all(|ch| ch.is_ascii_alphabetic())
+}
+
+fn parse_config(contents: &str) -> HashMap<String, String> {
+    contents.lines()
+        .map(|line| line.split('#').next().unwrap().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (key, value) = line.split_once('=')
+                .unwrap_or_else(|| panic!("Config lines should look like `key = value`, got: {line}"));
+            (String::from(key.trim()), String::from(value.trim()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults() {
+        let config = Config::from_parts("", &[]);
+        assert_eq!(config.get("cycles", 1_000_000_000), 1_000_000_000);
+        assert_eq!(config.get_list("times", vec![41, 96]), vec![41, 96]);
+        assert!(!config.flag("check"));
+    }
+
+    #[test]
+    fn file_then_flags() {
+        let contents = "# what if there were more red cubes?\nred = 20\ngreen=13 # unchanged\n\ntimes = 7, 15, 30\n";
+        let config = Config::from_parts(contents, &["--red=30", "--check", "part1"]);
+        assert_eq!(config.get("red", 12), 30);
+        assert_eq!(config.get("green", 0), 13);
+        assert_eq!(config.get_list("times", vec![0]), vec![7, 15, 30]);
+        assert!(config.flag("check"));
+        assert_eq!(config.positional(), &[String::from("part1")]);
+    }
+
+    #[test]
+    fn short_flags() {
+        let config = Config::from_parts("", &["-vv", "-q", "-5"]);
+        assert_eq!(config.get("v", 0), 2);
+        assert_eq!(config.get("q", 0), 1);
+        assert_eq!(config.positional(), &[String::from("-5")]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Couldn't parse config value red=lots")]
+    fn bad_value() {
+        let config = Config::from_parts("red = lots", &[]);
+        config.get("red", 12);
+    }
+}
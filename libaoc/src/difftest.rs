@@ -0,0 +1,267 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::Range;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+// what an implementation produced: either its answer, or the message it panicked with
+pub type Outcome<O> = Result<O, String>;
+
+// a tiny splitmix64 generator, so we don't need to pull in `rand` just for tests
+pub struct Rng {
+    state: u64
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    // a value in [start, end); panics if the range is empty
+    pub fn range(&mut self, range: Range<i64>) -> i64 {
+        assert!(!range.is_empty(), "Can't pick from an empty range: {:?}", range);
+        let width = range.end.abs_diff(range.start);
+        range.start.wrapping_add((self.next_u64() % width) as i64)
+    }
+}
+
+// the first generated input on which the two implementations disagreed,
+// along with the smallest version of it we could find that still disagrees
+#[derive(Debug)]
+pub struct Disagreement<I, O> {
+    pub seed: u64,
+    pub case: usize,
+    pub original: I,
+    pub input: I,
+    pub left: Outcome<O>,
+    pub right: Outcome<O>,
+    pub shrink_steps: usize,
+}
+
+impl<I: Debug, O: Debug> Display for Disagreement<I, O> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "implementations disagree (seed {}, case {})", self.seed, self.case)?;
+        writeln!(f, "  input: {:?}", self.input)?;
+        writeln!(f, "  left:  {:?}", self.left)?;
+        writeln!(f, "  right: {:?}", self.right)?;
+        write!(f, "  (minimized in {} steps from {:?})", self.shrink_steps, self.original)
+    }
+}
+
+type Generator<I> = Box<dyn Fn(&mut Rng) -> I>;
+type Shrinker<I> = Box<dyn Fn(&I) -> Vec<I>>;
+
+// Runs two implementations of the same question over generated inputs,
+// treating one as an oracle for the other.
+//
+//     Differential::new(|rng| rng.range(0..100))
+//         .shrink_with(|n| shrink_int(*n))
+//         .assert_agree(slow_answer, fast_answer);
+pub struct Differential<I> {
+    generator: Generator<I>,
+    shrinker: Shrinker<I>,
+    cases: usize,
+    seed: u64,
+}
+
+const DEFAULT_SEED: u64 = 2023;
+const DEFAULT_CASES: usize = 500;
+const MAX_SHRINK_STEPS: usize = 10_000;
+
+impl<I: Clone + Debug> Differential<I> {
+    pub fn new(generator: impl Fn(&mut Rng) -> I + 'static) -> Differential<I> {
+        Differential {
+            generator: Box::new(generator),
+            shrinker: Box::new(|_| vec![]),
+            cases: DEFAULT_CASES,
+            seed: DEFAULT_SEED,
+        }
+    }
+
+    // shrinker returns "smaller" candidates for an input; the first candidate that still
+    // disagrees is kept, and we repeat until none of the candidates disagree
+    pub fn shrink_with(mut self, shrinker: impl Fn(&I) -> Vec<I> + 'static) -> Differential<I> {
+        self.shrinker = Box::new(shrinker);
+        self
+    }
+
+    pub fn cases(mut self, cases: usize) -> Differential<I> {
+        self.cases = cases;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Differential<I> {
+        self.seed = seed;
+        self
+    }
+
+    pub fn run<O, A, B>(&self, left: A, right: B) -> Result<(), Disagreement<I, O>>
+    where
+        O: PartialEq + Debug,
+        A: Fn(&I) -> O,
+        B: Fn(&I) -> O,
+    {
+        let mut rng = Rng::new(self.seed);
+        for case in 0..self.cases {
+            let original = (self.generator)(&mut rng);
+            if let Some((left_out, right_out)) = disagreement(&original, &left, &right) {
+                return Err(self.minimize(case, original, left_out, right_out, &left, &right));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn assert_agree<O, A, B>(&self, left: A, right: B)
+    where
+        O: PartialEq + Debug,
+        A: Fn(&I) -> O,
+        B: Fn(&I) -> O,
+    {
+        if let Err(disagreement) = self.run(left, right) {
+            panic!("{disagreement}");
+        }
+    }
+
+    fn minimize<O, A, B>(&self, case: usize, original: I,
+                         left_out: Outcome<O>, right_out: Outcome<O>,
+                         left: &A, right: &B) -> Disagreement<I, O>
+    where
+        O: PartialEq + Debug,
+        A: Fn(&I) -> O,
+        B: Fn(&I) -> O,
+    {
+        let mut input = original.clone();
+        let mut outcomes = (left_out, right_out);
+        let mut shrink_steps = 0;
+        'shrinking: while shrink_steps < MAX_SHRINK_STEPS {
+            for candidate in (self.shrinker)(&input) {
+                if let Some(found) = disagreement(&candidate, left, right) {
+                    input = candidate;
+                    outcomes = found;
+                    shrink_steps += 1;
+                    continue 'shrinking;
+                }
+            }
+            // none of the smaller candidates disagree, so this is as small as it gets
+            break;
+        }
+
+        Disagreement {
+            seed: self.seed,
+            case,
+            original,
+            input,
+            left: outcomes.0,
+            right: outcomes.1,
+            shrink_steps,
+        }
+    }
+}
+
+fn disagreement<I, O, A, B>(input: &I, left: &A, right: &B) -> Option<(Outcome<O>, Outcome<O>)>
+where
+    O: PartialEq,
+    A: Fn(&I) -> O,
+    B: Fn(&I) -> O,
+{
+    let left_out = observe(|| left(input));
+    let right_out = observe(|| right(input));
+    if left_out == right_out {
+        None
+    } else {
+        Some((left_out, right_out))
+    }
+}
+
+// run an implementation, turning a panic into an Err so it can be reported like any other answer
+fn observe<O>(implementation: impl FnOnce() -> O) -> Outcome<O> {
+    catch_unwind(AssertUnwindSafe(implementation))
+        .map_err(|payload| {
+            payload.downcast_ref::<&str>().map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| String::from("<non-string panic>"))
+        })
+}
+
+// candidates closer to zero: zero itself, half-way there, and one step there
+pub fn shrink_int(value: i64) -> Vec<i64> {
+    if value == 0 {
+        return vec![];
+    }
+    let mut candidates = vec![0, value / 2, value - value.signum()];
+    candidates.dedup();
+    candidates.retain(|c| *c != value);
+    candidates
+}
+
+// candidates with one element removed, then candidates with one element shrunk
+pub fn shrink_vec<T: Clone>(items: &[T], shrink_item: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    let mut candidates = vec![];
+    for idx in 0..items.len() {
+        let mut fewer = items.to_vec();
+        fewer.remove(idx);
+        candidates.push(fewer);
+    }
+    for (idx, item) in items.iter().enumerate() {
+        for smaller in shrink_item(item) {
+            let mut shrunk = items.to_vec();
+            shrunk[idx] = smaller;
+            candidates.push(shrunk);
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng_range() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            let val = rng.range(-5..5);
+            assert!((-5..5).contains(&val));
+        }
+    }
+
+    #[test]
+    fn agreeing_implementations() {
+        let result = Differential::new(|rng| rng.range(0..1000))
+            .run(|n| n * 2, |n| n + n);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn minimizes_disagreement() {
+        let disagreement = Differential::new(|rng| rng.range(0..100_000))
+            .shrink_with(|n| shrink_int(*n))
+            .run(|n| *n < 100, |_| true)
+            .unwrap_err();
+        assert_eq!(disagreement.input, 100);
+        assert_eq!(disagreement.left, Ok(false));
+        assert_eq!(disagreement.right, Ok(true));
+    }
+
+    #[test]
+    fn reports_panics() {
+        let disagreement = Differential::new(|rng| rng.range(0..100))
+            .shrink_with(|n| shrink_int(*n))
+            .run(|n| if *n > 10 { panic!("too big: {n}") } else { *n }, |n| *n)
+            .unwrap_err();
+        assert_eq!(disagreement.input, 11);
+        assert_eq!(disagreement.left, Err(String::from("too big: 11")));
+    }
+
+    #[test]
+    fn shrinks_vecs() {
+        let candidates = shrink_vec(&[2, 0], |n| shrink_int(*n));
+        assert_eq!(candidates, vec![vec![0], vec![2], vec![0, 0], vec![1, 0]]);
+    }
+}
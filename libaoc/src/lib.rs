@@ -0,0 +1,4 @@
+pub mod animate;
+pub mod config;
+pub mod difftest;
+pub mod log;
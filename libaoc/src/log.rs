@@ -0,0 +1,151 @@
+use std::fmt::Arguments;
+use std::fs::File;
+use std::io::{stderr, Write};
+use std::sync::{Mutex, OnceLock};
+use crate::config::Config;
+
+// Diagnostics that can be switched on at runtime instead of commenting out println!s.
+//
+//   -v               show debug!() messages
+//   -vv              also show trace!() messages
+//   --log=d15,d03    only show messages from these targets, or modules inside them
+//   --log_file=path  write to a file instead of stderr
+//
+// The target defaults to the module the message came from, so each day is its own target;
+// pass `target: "..."` as the first argument to pick something narrower.
+// warn!() messages are always shown.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Level {
+    Warn, Debug, Trace
+}
+
+impl Level {
+    fn from_verbosity(verbosity: usize) -> Level {
+        match verbosity {
+            0 => Level::Warn,
+            1 => Level::Debug,
+            _ => Level::Trace,
+        }
+    }
+
+    fn label(&self) -> &str {
+        match self {
+            Level::Warn => "WARN",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+pub struct Logger {
+    level: Level,
+    targets: Vec<String>,
+    sink: Mutex<Box<dyn Write + Send>>,
+}
+
+impl Logger {
+    pub fn from_config(config: &Config) -> Logger {
+        let level = Level::from_verbosity(config.get("v", 0));
+        let targets = config.get_list("log", vec![]);
+        let sink: Box<dyn Write + Send> = match config.get("log_file", String::new()) {
+            path if path.is_empty() => Box::new(stderr()),
+            path => Box::new(File::create(&path)
+                .unwrap_or_else(|e| panic!("Couldn't open log file {path}: {e}"))),
+        };
+
+        Logger { level, targets, sink: Mutex::new(sink) }
+    }
+
+    pub fn enabled(&self, level: Level, target: &str) -> bool {
+        if level == Level::Warn {
+            return true;
+        }
+        level <= self.level &&
+            (self.targets.is_empty() || self.targets.iter().any(|t| within(target, t)))
+    }
+
+    fn write(&self, level: Level, target: &str, args: Arguments) {
+        let mut sink = self.sink.lock().unwrap();
+        // if we can't write diagnostics, there's nowhere to complain about it
+        let _ = writeln!(sink, "[{} {}] {}", level.label(), target, args);
+    }
+}
+
+// `d15` and `d15::bins` are within `d15`, but `d150` isn't
+fn within(target: &str, wanted: &str) -> bool {
+    target.strip_prefix(wanted).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+// Set up logging from an already-loaded config.
+// If nothing calls this, the first message loads the config itself.
+pub fn init(config: &Config) {
+    let _ = LOGGER.set(Logger::from_config(config));
+}
+
+fn logger() -> &'static Logger {
+    LOGGER.get_or_init(|| Logger::from_config(&Config::load()))
+}
+
+pub fn enabled(level: Level, target: &str) -> bool {
+    logger().enabled(level, target)
+}
+
+pub fn write(level: Level, target: &str, args: Arguments) {
+    logger().write(level, target, args)
+}
+
+#[macro_export]
+macro_rules! log {
+    ($level:expr, target: $target:expr, $($arg:tt)+) => {
+        if $crate::log::enabled($level, $target) {
+            $crate::log::write($level, $target, format_args!($($arg)+));
+        }
+    };
+    ($level:expr, $($arg:tt)+) => {
+        $crate::log!($level, target: module_path!(), $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Trace, $($arg)+) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_and_targets() {
+        let quiet = Logger::from_config(&Config::from_parts("", &[]));
+        assert!(quiet.enabled(Level::Warn, "d01"));
+        assert!(!quiet.enabled(Level::Debug, "d01"));
+
+        let verbose = Logger::from_config(&Config::from_parts("", &["-v"]));
+        assert!(verbose.enabled(Level::Debug, "d01"));
+        assert!(!verbose.enabled(Level::Trace, "d01"));
+
+        let targeted = Logger::from_config(&Config::from_parts("", &["-vv", "--log=d15"]));
+        assert!(targeted.enabled(Level::Trace, "d15"));
+        assert!(targeted.enabled(Level::Trace, "d15::bins"));
+        assert!(!targeted.enabled(Level::Debug, "d14"));
+        assert!(targeted.enabled(Level::Warn, "d14"));
+
+        // d1 shouldn't pick up d10 through d19
+        let short = Logger::from_config(&Config::from_parts("", &["-v", "--log=d1"]));
+        assert!(short.enabled(Level::Debug, "d1::scanner"));
+        assert!(!short.enabled(Level::Debug, "d11"));
+    }
+}